@@ -0,0 +1,184 @@
+//! Race Net - Headless multiplayer host and bot clients
+//!
+//! Runs a WebSocket race host without Tauri, or connects a bot player to
+//! one, so multiplayer can be exercised entirely on localhost.
+//!
+//! Usage:
+//!   race_net host [--addr 127.0.0.1:9001] [--runners N] [--distance M] [--seed S] [--players N]
+//!   race_net bot  [--url ws://127.0.0.1:9001] [--name NAME] [--speed M/S] [--target S]
+
+use app_lib::game_server::net::{ClientMessage, NetServer, ServerMessage};
+use app_lib::game_server::race::RaceConfig;
+use app_lib::game_server::runtime::{SimCommand, SimEvent, SimulationThread};
+use app_lib::game_server::simulation::{GameServer, GameState};
+use std::process;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use tungstenite::Message;
+
+const USAGE: &str = "\
+Usage:
+  race_net host [options]   Host a race and start it once enough players join
+    --addr <host:port>      Address to listen on (default 127.0.0.1:9001)
+    --runners <n>           Number of AI runners (default 0)
+    --distance <m>          Race distance in meters (default 1600)
+    --seed <s>              Race seed
+    --players <n>           Players to wait for before starting (default 1)
+
+  race_net bot [options]    Join a hosted race and run at a constant speed
+    --url <ws://...>        Host URL (default ws://127.0.0.1:9001)
+    --name <name>           Player name (default Bot)
+    --speed <m/s>           Desired speed (default 6)
+    --target <s>            Bot's own finish time for the distance, which sets
+                            how fast it can run before fading";
+
+/// How often the host checks whether enough players have joined
+const HOST_POLL: Duration = Duration::from_millis(50);
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let outcome = match args.next().as_deref() {
+        Some("host") => run_host(args),
+        Some("bot") => run_bot(args),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return;
+        }
+        _ => Err("expected 'host' or 'bot'".to_string()),
+    };
+
+    if let Err(message) = outcome {
+        eprintln!("race_net: {}\n\n{}", message, USAGE);
+        process::exit(2);
+    }
+}
+
+/// Host a race, start it once enough players joined, and print results as JSON
+fn run_host(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut addr = "127.0.0.1:9001".to_string();
+    let mut players = 1;
+    let mut config = RaceConfig {
+        runner_count: 0,
+        distance: 1600.0,
+        time_scale: 1.0,
+        lobby: true,
+        ..Default::default()
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--addr" => addr = value()?,
+            "--runners" => config.runner_count = parse_value(&arg, &value()?)?,
+            "--distance" => config.distance = parse_value(&arg, &value()?)?,
+            "--seed" => config.seed = Some(parse_value(&arg, &value()?)?),
+            "--players" => players = parse_value(&arg, &value()?)?,
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+
+    // Results arrive on the simulation thread once the race is over
+    let (results_tx, results_rx) = mpsc::channel();
+    let sim = Arc::new(SimulationThread::spawn(GameServer::new(), move |event| {
+        if let SimEvent::Results(results) = event {
+            let _ = results_tx.send(results);
+        }
+    }));
+    sim.call(move |server| server.init_race(config))
+        .and_then(|initialized| initialized)
+        .map_err(|e| e.to_string())?;
+
+    let mut host = NetServer::start(sim.clone(), addr.as_str()).map_err(|e| e.to_string())?;
+    eprintln!("race_net: hosting on ws://{}, waiting for {} player(s)", host.local_addr(), players);
+
+    // Wait for players, then race to the finish
+    loop {
+        let (state, joined) = sim
+            .call(|server| {
+                let joined = server
+                    .get_snapshot()
+                    .map(|s| s.runners.iter().filter(|r| r.player).count())
+                    .unwrap_or(0);
+                (server.get_state(), joined)
+            })
+            .map_err(|e| e.to_string())?;
+
+        if state == GameState::Ready && joined >= players {
+            sim.control(SimCommand::Start).map_err(|e| e.to_string())?;
+            break;
+        }
+        thread::sleep(HOST_POLL);
+    }
+
+    let results = results_rx.recv().map_err(|e| e.to_string())?;
+    host.stop();
+
+    println!("{}", serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+/// Join a hosted race and run at constant speed until results arrive
+fn run_bot(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut url = "ws://127.0.0.1:9001".to_string();
+    let mut name = "Bot".to_string();
+    let mut speed: f32 = 6.0;
+    let mut target_time = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--url" => url = value()?,
+            "--name" => name = value()?,
+            "--speed" => speed = parse_value(&arg, &value()?)?,
+            "--target" => target_time = Some(parse_value(&arg, &value()?)?),
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+
+    let (mut ws, _) = tungstenite::connect(url.as_str()).map_err(|e| e.to_string())?;
+    send(&mut ws, &ClientMessage::Join { name: name.clone(), target_time })?;
+
+    let mut runner_id = None;
+    loop {
+        let text = match ws.read().map_err(|e| e.to_string())? {
+            Message::Text(text) => text,
+            Message::Close(_) => return Err("host closed the connection".to_string()),
+            _ => continue,
+        };
+
+        match serde_json::from_str(&text).map_err(|e| e.to_string())? {
+            ServerMessage::Joined { runner_id: id } => {
+                eprintln!("race_net: {} joined as runner {}", name, id);
+                runner_id = Some(id);
+                send(&mut ws, &ClientMessage::Input { desired_speed: speed, lane_change: 0.0 })?;
+            }
+            ServerMessage::Results { results } => {
+                let own = results.iter().find(|r| Some(r.runner_id) == runner_id);
+                println!("{}", serde_json::to_string(&own).map_err(|e| e.to_string())?);
+                send(&mut ws, &ClientMessage::Leave)?;
+                return Ok(());
+            }
+            ServerMessage::Error { message } => return Err(message),
+            ServerMessage::Snapshot { .. } => {}
+        }
+    }
+}
+
+/// Send a client message as a JSON text frame
+fn send<S: std::io::Read + std::io::Write>(
+    ws: &mut tungstenite::WebSocket<S>,
+    message: &ClientMessage,
+) -> Result<(), String> {
+    let text = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    ws.send(Message::Text(text)).map_err(|e| e.to_string())
+}
+
+/// Parse a single flag value
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, flag))
+}
@@ -0,0 +1,205 @@
+//! Race Sim - Headless batch race simulator
+//!
+//! Runs races to completion without Tauri or a window and prints the
+//! finish order of each race as JSON or CSV. Races run in real time
+//! (time scale 1) unless told otherwise; the time scale only slows the
+//! runners down per step and never changes the recorded times.
+//!
+//! Usage:
+//!   race_sim [--config race.json] [--runners N] [--distance M]
+//!            [--time-scale X] [--seed S] [--races N] [--format json|csv]
+
+use app_lib::game_server::race::{Race, RaceConfig, RaceResult};
+use serde::Serialize;
+use std::io::{self, BufWriter, Write};
+use std::process;
+
+/// Sim-time step used for every race (matches the server's default tick rate)
+const FIXED_DELTA: f32 = 1.0 / 60.0;
+
+const USAGE: &str = "\
+Usage: race_sim [options]
+
+Options:
+  --config <path>      Load RaceConfig from a JSON file
+  --runners <n>        Number of runners
+  --distance <m>       Race distance in meters
+  --time-scale <x>     Slow-motion factor (default 1): runners and the race clock
+                       both run x times slower than real time, so recorded
+                       times are the same and only the steps per race change
+  --seed <s>           Seed for the first race (later races use seed+1, seed+2, ...)
+  --races <n>          Number of races to run (default 1)
+  --format <json|csv>  Output format (default json)
+  -h, --help           Show this help";
+
+/// Output format for finish orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    Csv,
+}
+
+/// Parsed command line options
+struct Options {
+    config: RaceConfig,
+    races: u32,
+    format: OutputFormat,
+}
+
+/// Finish order of a single simulated race
+#[derive(Debug, Serialize)]
+struct BatchRace {
+    race: u32,
+    seed: u64,
+    finish_order: Vec<RaceResult>,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("race_sim: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let results = match run_batch(&options) {
+        Ok(results) => results,
+        Err(message) => {
+            eprintln!("race_sim: {}", message);
+            process::exit(1);
+        }
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let written = match options.format {
+        OutputFormat::Json => write_json(&mut out, &results),
+        OutputFormat::Csv => write_csv(&mut out, &results),
+    };
+
+    if let Err(e) = written.and_then(|_| out.flush()) {
+        eprintln!("race_sim: failed to write output: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Parse command line flags into options
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    // Slow motion only adds steps to a batch run, so run in real time
+    let mut config = RaceConfig { time_scale: 1.0, ..RaceConfig::default() };
+    let mut races = 1;
+    let mut format = OutputFormat::Json;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--config" => {
+                let path = value()?;
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("cannot read {}: {}", path, e))?;
+                config = serde_json::from_str(&text)
+                    .map_err(|e| format!("invalid config {}: {}", path, e))?;
+            }
+            "--runners" => config.runner_count = parse_value(&arg, &value()?)?,
+            "--distance" => config.distance = parse_value(&arg, &value()?)?,
+            "--time-scale" => config.time_scale = parse_value(&arg, &value()?)?,
+            "--seed" => config.seed = Some(parse_value(&arg, &value()?)?),
+            "--races" => races = parse_value(&arg, &value()?)?,
+            "--format" => {
+                format = match value()?.as_str() {
+                    "json" => OutputFormat::Json,
+                    "csv" => OutputFormat::Csv,
+                    other => return Err(format!("unknown format '{}'", other)),
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+
+    // A zero time scale would never finish, and an empty field finishes
+    // instantly. Nobody joins a batch run, so there's no lobby to wait for.
+    config.lobby = false;
+    config.validate().map_err(|e| e.to_string())?;
+    if config.player.is_some() {
+        return Err("config has a player, but nobody steers them in a batch run".to_string());
+    }
+
+    Ok(Options { config, races, format })
+}
+
+/// Parse a single flag value
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, flag))
+}
+
+/// Run every race in the batch to completion
+fn run_batch(options: &Options) -> Result<Vec<BatchRace>, String> {
+    let base_seed = options.config.seed.unwrap_or_else(rand::random);
+
+    (0..options.races)
+        .map(|i| {
+            let config = RaceConfig {
+                seed: Some(base_seed.wrapping_add(i as u64)),
+                ..options.config.clone()
+            };
+
+            let mut race = Race::new(config);
+            race.generate_runners();
+            race.setup_starting_positions();
+            if !race.run_to_finish(FIXED_DELTA) {
+                return Err(format!("race {} (seed {}) did not finish", i + 1, race.seed));
+            }
+
+            Ok(BatchRace {
+                race: i + 1,
+                seed: race.seed,
+                finish_order: race.finish_order,
+            })
+        })
+        .collect()
+}
+
+/// Write results as a JSON array of races
+fn write_json(out: &mut impl Write, results: &[BatchRace]) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, results)?;
+    writeln!(out)
+}
+
+/// Write results as CSV, one row per finisher
+fn write_csv(out: &mut impl Write, results: &[BatchRace]) -> io::Result<()> {
+    writeln!(out, "race,seed,position,runner_id,runner_name,finish_time")?;
+
+    for race in results {
+        for result in &race.finish_order {
+            writeln!(
+                out,
+                "{},{},{},{},{},{:.3}",
+                race.race,
+                race.seed,
+                result.position,
+                result.runner_id,
+                csv_field(&result.runner_name),
+                result.finish_time,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Quote a CSV field if it contains separators or quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
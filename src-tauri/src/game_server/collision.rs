@@ -0,0 +1,159 @@
+//! Collision - Runner-vs-runner collision resolution
+//!
+//! Port of the frontend CollisionSystem. Runners are hashed into a grid
+//! over (distance, lateral offset) so each runner is only checked against
+//! its neighbours, keeping the pass O(n) for large fields.
+
+use std::collections::HashMap;
+use crate::game_server::runner::RunnerState;
+use crate::game_server::track::LANE_WIDTH;
+
+/// Spatial hash grid over track coordinates (meters along track, meters from inside edge)
+#[derive(Debug, Clone)]
+pub struct SpatialHashGrid {
+    inv_cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHashGrid {
+    /// Create a grid with the given cell size (meters)
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            inv_cell_size: 1.0 / cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Remove all entries, keeping cell allocations for reuse
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    fn cell_key(&self, along: f32, across: f32) -> (i32, i32) {
+        (
+            (along * self.inv_cell_size).floor() as i32,
+            (across * self.inv_cell_size).floor() as i32,
+        )
+    }
+
+    /// Rebuild the grid from all unfinished runners (indexed by slice position)
+    pub fn rebuild(&mut self, runners: &[RunnerState]) {
+        self.clear();
+        for (i, runner) in runners.iter().enumerate() {
+            if !runner.flags.finished {
+                self.insert(i, runner.distance, runner.lane_position * LANE_WIDTH);
+            }
+        }
+    }
+
+    /// Insert an entry at the given position
+    pub fn insert(&mut self, index: usize, along: f32, across: f32) {
+        let key = self.cell_key(along, across);
+        self.cells.entry(key).or_default().push(index);
+    }
+
+    /// Collect all entries in the same and adjacent cells into `out`
+    pub fn nearby(&self, along: f32, across: f32, out: &mut Vec<usize>) {
+        out.clear();
+        let (cell_x, cell_z) = self.cell_key(along, across);
+
+        // Check 3x3 grid around query point
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                if let Some(cell) = self.cells.get(&(cell_x + dx, cell_z + dz)) {
+                    out.extend_from_slice(cell);
+                }
+            }
+        }
+    }
+}
+
+impl Default for SpatialHashGrid {
+    fn default() -> Self {
+        Self::new(5.0)
+    }
+}
+
+/// Collision resolution logic
+pub struct Collision;
+
+impl Collision {
+    /// Constants
+    const COLLISION_RADIUS: f32 = 0.4;
+    const PUSH_STRENGTH: f32 = 3.0;
+    /// Fraction of speed lost per second while in contact with the runner ahead
+    const CONTACT_DRAG: f32 = 0.6;
+    const MAX_LANE: f32 = 3.0;
+    /// Lane window (lane units) in which a runner on the outside blocks a move out
+    const ALONGSIDE_MIN_LANE_GAP: f32 = 0.3;
+    const ALONGSIDE_MAX_LANE_GAP: f32 = 1.0;
+    const ALONGSIDE_MAX_DISTANCE: f32 = 0.5;
+
+    /// Resolve collisions between all unfinished runners for one tick.
+    ///
+    /// The runner behind in each overlapping pair is pushed outward (unless
+    /// it is lane-locked) and slowed. Runners with contact ahead and someone
+    /// alongside on the outside are flagged as squished (boxed in). `grid`
+    /// must have been rebuilt from `runners` this tick.
+    pub fn resolve(runners: &mut [RunnerState], grid: &SpatialHashGrid, delta: f32) {
+        let min_dist = Self::COLLISION_RADIUS * 2.0;
+        let push_amount = Self::PUSH_STRENGTH * delta;
+        let mut blocked_ahead = vec![false; runners.len()];
+        let mut blocked_outside = vec![false; runners.len()];
+        let mut nearby = Vec::new();
+
+        for i in 0..runners.len() {
+            if runners[i].flags.finished {
+                continue;
+            }
+
+            let along = runners[i].distance;
+            let across = runners[i].lane_position * LANE_WIDTH;
+            grid.nearby(along, across, &mut nearby);
+
+            for &j in &nearby {
+                if i >= j {
+                    continue; // Avoid duplicates
+                }
+
+                let distance_diff = runners[j].distance - runners[i].distance;
+                let lane_diff = runners[j].lane_position - runners[i].lane_position;
+
+                // Someone alongside on the outside blocks moving out
+                if distance_diff.abs() < Self::ALONGSIDE_MAX_DISTANCE {
+                    if (Self::ALONGSIDE_MIN_LANE_GAP..Self::ALONGSIDE_MAX_LANE_GAP).contains(&lane_diff) {
+                        blocked_outside[i] = true;
+                    } else if (Self::ALONGSIDE_MIN_LANE_GAP..Self::ALONGSIDE_MAX_LANE_GAP).contains(&-lane_diff) {
+                        blocked_outside[j] = true;
+                    }
+                }
+
+                let lateral = lane_diff * LANE_WIDTH;
+                let dist = (distance_diff * distance_diff + lateral * lateral).sqrt();
+                if dist >= min_dist {
+                    continue;
+                }
+
+                // The runner behind yields (the later runner on an exact tie)
+                let behind = if distance_diff > 0.0 { i } else { j };
+                let overlap = min_dist - dist;
+
+                let runner = &mut runners[behind];
+                // Pushes stop at MAX_LANE, but never pull a runner who starts
+                // wider (alley and staggered starts) back in
+                if !runner.is_lane_locked() {
+                    let max_lane = Self::MAX_LANE.max(runner.lane_position);
+                    runner.lane_position = (runner.lane_position + overlap * push_amount).min(max_lane);
+                }
+                runner.current_speed *= 1.0 - (Self::CONTACT_DRAG * delta).min(1.0);
+                blocked_ahead[behind] = true;
+            }
+        }
+
+        for (i, runner) in runners.iter_mut().enumerate() {
+            runner.flags.squished = !runner.flags.finished && blocked_ahead[i] && blocked_outside[i];
+        }
+    }
+}
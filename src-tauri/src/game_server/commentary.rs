@@ -0,0 +1,312 @@
+//! Commentary - Play-by-play text from race events
+//!
+//! A `Commentator` turns the `RaceEvent`s of each tick into timestamped
+//! lines ("Runner 12 takes the lead with 600 m to go"), throttled so the
+//! feed stays readable. Wording comes from a `Phrasebook`; English is
+//! built in and other languages load from JSON files (see `phrasebooks/`).
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::game_server::events::RaceEvent;
+use crate::game_server::race::Race;
+
+/// Kinds of line the commentator can say
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phrase {
+    /// The gun went
+    RaceStart,
+    /// `{runner}` took the lead with `{to_go}` m left
+    TakesLead,
+    /// `{runner}` passed `{other}` for `{place}`
+    Pass,
+    /// `{runner}` leads at the bell
+    BellLap,
+    /// `{runner}` leads `{other}` by `{gap}` s with `{laps}` laps to go
+    Gap,
+    /// A lead group of `{count}` led by `{runner}`, the chase `{gap}` s down, `{laps}` laps to go
+    ChasePack,
+    /// `{runner}` won in `{time}`
+    Winner,
+    /// `{runner}` took `{place}` in `{time}`
+    Finisher,
+    /// Everyone is home
+    RaceFinished,
+}
+
+/// Commentary wording for one language. Each phrase has one or more
+/// templates, used in turn; `{name}` placeholders are filled in per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Phrasebook {
+    /// Language code (e.g. "en")
+    pub language: String,
+    /// Templates per phrase. Missing phrases fall back to English.
+    pub phrases: BTreeMap<Phrase, Vec<String>>,
+    /// Names for 1st, 2nd, 3rd... (plain numbers beyond the list)
+    #[serde(default)]
+    pub ordinals: Vec<String>,
+}
+
+impl Phrasebook {
+    /// Built-in English phrasebook
+    pub fn english() -> Self {
+        let phrases = [
+            (Phrase::RaceStart, vec!["And they're off!", "The gun goes and the field is away."]),
+            (Phrase::TakesLead, vec![
+                "{runner} takes the lead with {to_go} m to go.",
+                "{runner} hits the front, {to_go} m remaining.",
+            ]),
+            (Phrase::Pass, vec!["{runner} moves past {other} into {place}.", "{runner} goes by {other}, up to {place}."]),
+            (Phrase::BellLap, vec!["The bell rings for {runner}, one lap to go!"]),
+            (Phrase::Gap, vec![
+                "{laps} laps to go, {runner} leads {other} by {gap} seconds.",
+                "{runner} is {gap} seconds clear of {other} with {laps} laps left.",
+            ]),
+            (Phrase::ChasePack, vec![
+                "{laps} laps to go: {runner} heads a lead group of {count}, the chase pack is {gap} seconds down.",
+                "{count} together at the front behind {runner}, {gap} seconds back to the chasers. {laps} laps to go.",
+            ]),
+            (Phrase::Winner, vec!["{runner} wins it in {time}!", "Victory for {runner} in {time}!"]),
+            (Phrase::Finisher, vec!["{runner} takes {place} in {time}."]),
+            (Phrase::RaceFinished, vec!["The last runner is home. That's the race!"]),
+        ];
+        let ordinals = ["1st", "2nd", "3rd", "4th", "5th", "6th", "7th", "8th", "9th", "10th"];
+
+        Self {
+            language: "en".to_string(),
+            phrases: phrases
+                .into_iter()
+                .map(|(phrase, templates)| (phrase, templates.into_iter().map(String::from).collect()))
+                .collect(),
+            ordinals: ordinals.into_iter().map(String::from).collect(),
+        }
+    }
+
+    /// Read a phrasebook from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let book: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if let Some((phrase, _)) = book.phrases.iter().find(|(_, templates)| templates.is_empty()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Phrase {:?} has no templates", phrase),
+            ));
+        }
+        Ok(book)
+    }
+
+    /// Templates for a phrase, or `None` if the book doesn't have it
+    fn templates(&self, phrase: Phrase) -> Option<&[String]> {
+        self.phrases.get(&phrase).map(Vec::as_slice).filter(|t| !t.is_empty())
+    }
+
+    /// Name for a 1-based place
+    fn ordinal(&self, place: u32) -> String {
+        place
+            .checked_sub(1)
+            .and_then(|i| self.ordinals.get(i as usize))
+            .cloned()
+            .unwrap_or_else(|| place.to_string())
+    }
+}
+
+impl Default for Phrasebook {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+/// One line of commentary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentaryLine {
+    /// Race time (seconds)
+    pub time: f32,
+    pub phrase: Phrase,
+    pub text: String,
+}
+
+/// Placeholder values for a template
+type Args = Vec<(&'static str, String)>;
+
+/// How much a line matters when the feed is busy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Priority {
+    /// Always said
+    High,
+    /// Dropped if the last line was too recent
+    Normal,
+}
+
+/// Turns race events into commentary
+#[derive(Debug, Clone)]
+pub struct Commentator {
+    book: Phrasebook,
+    english: Phrasebook,
+    /// Race time of the last line said
+    last_line: Option<f32>,
+    /// Lines said so far, used to rotate through templates
+    said: usize,
+}
+
+impl Commentator {
+    /// Least real time between two normal-priority lines (seconds)
+    const MIN_INTERVAL: f32 = 4.0;
+    /// Passes further down the field than this aren't mentioned
+    const PASS_PLACES: u32 = 3;
+    /// Runners within this distance of the one ahead run in the same group (m)
+    const GROUP_GAP: f32 = 3.0;
+
+    pub fn new(book: Phrasebook) -> Self {
+        Self {
+            book,
+            english: Phrasebook::english(),
+            last_line: None,
+            said: 0,
+        }
+    }
+
+    /// Language of the phrasebook in use
+    pub fn language(&self) -> &str {
+        &self.book.language
+    }
+
+    /// Switch phrasebook, keeping track of the race in progress
+    pub fn set_phrasebook(&mut self, book: Phrasebook) {
+        self.book = book;
+    }
+
+    /// Forget the last race, ready for the next
+    pub fn reset(&mut self) {
+        self.last_line = None;
+        self.said = 0;
+    }
+
+    /// Lines for the events of one tick. `race` is the state after them.
+    pub fn comment(&mut self, race: &Race, events: &[RaceEvent]) -> Vec<CommentaryLine> {
+        let mut lines = Vec::new();
+        // Event times are race time, which runs `time_scale` times slower
+        // than the real time the throttle is measured in
+        let min_interval = Self::MIN_INTERVAL / race.config.time_scale;
+
+        for event in events {
+            let Some((priority, phrase, args)) = self.describe(race, event) else { continue };
+
+            let time = event.time();
+            let recent = self.last_line.is_some_and(|last| time - last < min_interval);
+            if priority == Priority::Normal && recent {
+                continue;
+            }
+
+            lines.push(CommentaryLine { time, phrase, text: self.fill(phrase, &args) });
+            self.last_line = Some(time);
+            self.said += 1;
+        }
+        lines
+    }
+
+    /// What to say about an event, if anything
+    fn describe(&self, race: &Race, event: &RaceEvent) -> Option<(Priority, Phrase, Args)> {
+        let name = |id: u32| race.get_runner(id).map_or_else(|| format!("Runner {}", id + 1), |r| r.name.clone());
+
+        let line = match *event {
+            RaceEvent::RaceStart { .. } => (Priority::High, Phrase::RaceStart, vec![]),
+            RaceEvent::LeadChange { runner_id, to_go, .. } => (
+                Priority::Normal,
+                Phrase::TakesLead,
+                vec![("runner", name(runner_id)), ("to_go", Self::meters(to_go))],
+            ),
+            RaceEvent::Pass { runner_id, passed_id, place, .. } if place > 1 && place <= Self::PASS_PLACES => (
+                Priority::Normal,
+                Phrase::Pass,
+                vec![("runner", name(runner_id)), ("other", name(passed_id)), ("place", self.book.ordinal(place))],
+            ),
+            RaceEvent::BellLap { runner_id, .. } => (Priority::High, Phrase::BellLap, vec![("runner", name(runner_id))]),
+            RaceEvent::LapCompleted { runner_id, laps_to_go, .. } if laps_to_go > 1 => {
+                // Only the leader's laps, as a chance to sum up the gaps
+                let mut order = race.running_order();
+                if order.next()?.id != runner_id {
+                    return None;
+                }
+                return self.describe_gaps(race, laps_to_go).map(|(phrase, args)| (Priority::Normal, phrase, args));
+            }
+            RaceEvent::RunnerFinished { runner_id, position, time } if position <= Self::PASS_PLACES => {
+                let args = vec![
+                    ("runner", name(runner_id)),
+                    ("place", self.book.ordinal(position)),
+                    ("time", Self::clock(time)),
+                ];
+                if position == 1 {
+                    (Priority::High, Phrase::Winner, args)
+                } else {
+                    (Priority::Normal, Phrase::Finisher, args)
+                }
+            }
+            RaceEvent::RaceFinished { .. } => (Priority::High, Phrase::RaceFinished, vec![]),
+            _ => return None,
+        };
+        Some(line)
+    }
+
+    /// Sum up the front of the race: the leader's margin, or the size of
+    /// the lead group and how far back the chasers are
+    fn describe_gaps(&self, race: &Race, laps_to_go: u32) -> Option<(Phrase, Args)> {
+        let order: Vec<_> = race.running_order().filter(|r| !r.flags.finished).collect();
+        let leader = order.first()?;
+
+        // Average race pace so far turns meters into seconds
+        let pace = leader.progress() / race.elapsed_time.max(f32::EPSILON);
+        let group = order
+            .windows(2)
+            .position(|pair| pair[0].progress() - pair[1].progress() > Self::GROUP_GAP)
+            .map_or(order.len(), |i| i + 1);
+        let chaser = order.get(group)?;
+        let gap = (order[group - 1].progress() - chaser.progress()) / pace.max(f32::EPSILON);
+
+        let mut args = vec![
+            ("runner", leader.name.clone()),
+            ("laps", laps_to_go.to_string()),
+            ("gap", format!("{:.1}", gap)),
+        ];
+        if group == 1 {
+            args.push(("other", chaser.name.clone()));
+            Some((Phrase::Gap, args))
+        } else {
+            args.push(("count", group.to_string()));
+            Some((Phrase::ChasePack, args))
+        }
+    }
+
+    /// Fill the next template for `phrase`
+    fn fill(&self, phrase: Phrase, args: &[(&str, String)]) -> String {
+        let templates = self
+            .book
+            .templates(phrase)
+            .or_else(|| self.english.templates(phrase))
+            .unwrap_or_default();
+        let Some(template) = templates.get(self.said % templates.len().max(1)) else {
+            return String::new();
+        };
+
+        args.iter()
+            .fold(template.clone(), |text, (key, value)| text.replace(&format!("{{{}}}", key), value))
+    }
+
+    /// Distance to go, to the nearest 10 m
+    fn meters(to_go: f32) -> String {
+        format!("{}", ((to_go / 10.0).round() * 10.0).max(0.0) as u32)
+    }
+
+    /// Race time as m:ss.s
+    fn clock(time: f32) -> String {
+        let tenths = (time.max(0.0) * 10.0).round() as u32;
+        format!("{}:{:02}.{}", tenths / 600, tenths % 600 / 10, tenths % 10)
+    }
+}
+
+impl Default for Commentator {
+    fn default() -> Self {
+        Self::new(Phrasebook::english())
+    }
+}
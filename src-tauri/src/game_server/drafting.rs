@@ -0,0 +1,42 @@
+//! Drafting - Slipstream detection for runners
+//!
+//! A runner sitting closely behind another in a similar lane position
+//! is drafting and can hold pace more cheaply (see `Runner::update`).
+//! Mirrors `Runner.isDraftingBehind` from the JS frontend.
+
+use crate::game_server::collision::SpatialHashGrid;
+use crate::game_server::runner::RunnerState;
+use crate::game_server::track::LANE_WIDTH;
+
+/// Drafting detection logic
+pub struct Drafting;
+
+impl Drafting {
+    /// Constants
+    const MIN_GAP: f32 = 0.5;
+    const MAX_GAP: f32 = 2.0;
+    const MAX_LANE_DIFF: f32 = 0.3;
+
+    /// Set `flags.drafting` on every runner that is sitting behind another.
+    /// `grid` must have been rebuilt from `runners` this tick.
+    pub fn detect(runners: &mut [RunnerState], grid: &SpatialHashGrid) {
+        let mut nearby = Vec::new();
+
+        for i in 0..runners.len() {
+            if runners[i].flags.finished {
+                runners[i].flags.drafting = false;
+                continue;
+            }
+
+            let distance = runners[i].distance;
+            let lane = runners[i].lane_position;
+            grid.nearby(distance, lane * LANE_WIDTH, &mut nearby);
+
+            runners[i].flags.drafting = nearby.iter().any(|&j| {
+                let gap = runners[j].distance - distance;
+                let lane_diff = (runners[j].lane_position - lane).abs();
+                j != i && gap > Self::MIN_GAP && gap < Self::MAX_GAP && lane_diff < Self::MAX_LANE_DIFF
+            });
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Energy - Fatigue models for AI runners
+//!
+//! Port of the frontend EnergySystem for simulated runners. Rates are
+//! expressed per meter at the runner's planned pace (intensity 1.0), so
+//! the models behave the same regardless of time scale or tick rate.
+
+use serde::{Deserialize, Serialize};
+
+/// Which energy model runners use in a race
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EnergyType {
+    /// No fatigue, runners follow their splits
+    #[default]
+    None,
+    /// Sprint model: lactic acid builds with intensity and causes a fade
+    LacticAcid,
+    /// Distance model: stamina depletes, converts to a kick for the final lap
+    StaminaKick,
+}
+
+impl EnergyType {
+    /// Energy model matching the frontend race mode for a distance
+    pub fn for_distance(distance: f32) -> Self {
+        if distance <= 400.0 {
+            Self::LacticAcid
+        } else {
+            Self::StaminaKick
+        }
+    }
+}
+
+/// Lactic acid (400m): fills faster the harder you run, drains when easing off
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LacticAcid {
+    /// Level (0-100)
+    pub level: f32,
+}
+
+impl LacticAcid {
+    /// Level reached at the finish when running exactly on planned pace
+    const FILL_AT_PACE: f32 = 85.0;
+    const FILL_EXPONENT: i32 = 2;
+    /// Intensity below which lactic acid drains
+    const RECOVERY_INTENSITY: f32 = 0.8;
+    /// Drain per meter at zero intensity
+    const DRAIN_PER_METER: f32 = 0.3;
+    /// Level at which the runner starts to fade
+    const PENALTY_ONSET: f32 = 70.0;
+    /// Speed penalty at 100% lactic acid
+    const PENALTY_MAX: f32 = 0.5;
+
+    fn update(&mut self, moved: f32, intensity: f32, race_distance: f32) {
+        let fill = intensity.powi(Self::FILL_EXPONENT) * Self::FILL_AT_PACE / race_distance * moved;
+
+        let drain = if intensity < Self::RECOVERY_INTENSITY {
+            let slowness = (Self::RECOVERY_INTENSITY - intensity) / Self::RECOVERY_INTENSITY;
+            slowness * Self::DRAIN_PER_METER * moved
+        } else {
+            0.0
+        };
+
+        self.level = (self.level + fill - drain).clamp(0.0, 100.0);
+    }
+
+    fn speed_multiplier(&self) -> f32 {
+        if self.level <= Self::PENALTY_ONSET {
+            return 1.0;
+        }
+
+        let saturation = (self.level - Self::PENALTY_ONSET) / (100.0 - Self::PENALTY_ONSET);
+        1.0 - saturation * Self::PENALTY_MAX
+    }
+}
+
+/// Stamina (1600m+): depletes over the race, converts to a kick bar for the final lap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stamina {
+    /// Stamina (0-100)
+    pub stamina: f32,
+    /// Kick bar (0-100, only active in kick phase)
+    pub kick_bar: f32,
+    /// Whether the runner is on the final lap kick
+    pub in_kick_phase: bool,
+}
+
+impl Stamina {
+    /// Stamina used by the kick phase when running exactly on planned pace
+    const USED_AT_PACE: f32 = 70.0;
+    /// Drain multiplier while drafting
+    const DRAFT_REDUCTION: f32 = 0.5;
+    /// Kick bar = 2x remaining stamina
+    const KICK_MULTIPLIER: f32 = 2.0;
+    /// Final lap length
+    const KICK_PHASE_LENGTH: f32 = 400.0;
+    /// Speed boost while the kick bar has anything left
+    const KICK_SPEED_BONUS: f32 = 1.08;
+    /// Speed once the kick bar is empty
+    const KICK_EMPTY_FACTOR: f32 = 0.92;
+
+    fn update(&mut self, moved: f32, intensity: f32, drafting: bool, distance: f32, race_distance: f32) {
+        let kick_start = (race_distance - Self::KICK_PHASE_LENGTH).max(0.0);
+
+        if !self.in_kick_phase && distance >= kick_start {
+            self.kick_bar = (self.stamina * Self::KICK_MULTIPLIER).min(100.0);
+            self.in_kick_phase = true;
+        }
+
+        let effort = intensity * intensity * moved;
+
+        if self.in_kick_phase {
+            let depletion = effort * 100.0 / Self::KICK_PHASE_LENGTH;
+            self.kick_bar = (self.kick_bar - depletion).max(0.0);
+        } else {
+            let mut depletion = effort * Self::USED_AT_PACE / kick_start.max(1.0);
+            if drafting {
+                depletion *= Self::DRAFT_REDUCTION;
+            }
+            self.stamina = (self.stamina - depletion).max(0.0);
+        }
+    }
+
+    fn speed_multiplier(&self) -> f32 {
+        if !self.in_kick_phase {
+            1.0
+        } else if self.kick_bar > 0.0 {
+            Self::KICK_SPEED_BONUS
+        } else {
+            Self::KICK_EMPTY_FACTOR
+        }
+    }
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            stamina: 100.0,
+            kick_bar: 0.0,
+            in_kick_phase: false,
+        }
+    }
+}
+
+/// Per-runner energy state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum EnergySystem {
+    #[default]
+    None,
+    LacticAcid(LacticAcid),
+    StaminaKick(Stamina),
+}
+
+impl EnergySystem {
+    /// Create a fresh energy state for the given model
+    pub fn new(energy_type: EnergyType) -> Self {
+        match energy_type {
+            EnergyType::None => Self::None,
+            EnergyType::LacticAcid => Self::LacticAcid(LacticAcid::default()),
+            EnergyType::StaminaKick => Self::StaminaKick(Stamina::default()),
+        }
+    }
+
+    /// Which model this state belongs to
+    pub fn energy_type(&self) -> EnergyType {
+        match self {
+            Self::None => EnergyType::None,
+            Self::LacticAcid(_) => EnergyType::LacticAcid,
+            Self::StaminaKick(_) => EnergyType::StaminaKick,
+        }
+    }
+
+    /// Reset to a fresh state for the same model
+    pub fn reset(&mut self) {
+        *self = Self::new(self.energy_type());
+    }
+
+    /// Update after moving `moved` meters at `intensity` (current / planned speed)
+    pub fn update(&mut self, moved: f32, intensity: f32, drafting: bool, distance: f32, race_distance: f32) {
+        match self {
+            Self::None => {}
+            Self::LacticAcid(lactic) => lactic.update(moved, intensity, race_distance),
+            Self::StaminaKick(stamina) => stamina.update(moved, intensity, drafting, distance, race_distance),
+        }
+    }
+
+    /// Multiplier applied to the runner's planned speed
+    pub fn speed_multiplier(&self) -> f32 {
+        match self {
+            Self::None => 1.0,
+            Self::LacticAcid(lactic) => lactic.speed_multiplier(),
+            Self::StaminaKick(stamina) => stamina.speed_multiplier(),
+        }
+    }
+
+    /// Display value (0-100): lactic acid level, stamina, or kick bar
+    pub fn display_value(&self) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::LacticAcid(lactic) => lactic.level,
+            Self::StaminaKick(stamina) if stamina.in_kick_phase => stamina.kick_bar,
+            Self::StaminaKick(stamina) => stamina.stamina,
+        }
+    }
+
+    /// Whether the runner is in the final lap kick
+    pub fn is_kicking(&self) -> bool {
+        matches!(self, Self::StaminaKick(stamina) if stamina.in_kick_phase)
+    }
+}
@@ -0,0 +1,129 @@
+//! Error - Errors returned by the game server
+//!
+//! Every Tauri command fails with a `GameError`. It serializes as
+//! `{ code, message }`: the code is stable for the frontend to branch on,
+//! the message is for people.
+
+use std::fmt;
+use std::io;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use crate::game_server::state::{GameState, RaceStatus};
+
+/// Game server error
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    /// A race configuration value is out of range
+    InvalidConfig { field: &'static str, reason: String },
+    /// The command can't be used in the current game state
+    InvalidState { command: &'static str, state: GameState },
+    /// The game state machine has no such transition
+    InvalidTransition { from: GameState, to: GameState },
+    /// The race status machine has no such transition
+    InvalidRaceTransition { from: RaceStatus, to: RaceStatus },
+    /// No race has been initialized
+    NoRace,
+    /// The race has nobody in it to start
+    NoRunners,
+    /// The current race has no human player
+    NoPlayer,
+    /// No replay is recorded or loaded
+    NoReplay,
+    /// No athlete roster is loaded
+    NoRoster,
+    /// The roster has no athlete with this bib
+    UnknownAthlete(u32),
+    /// No series is in progress
+    NoSeries,
+    /// Every race in the series has been run
+    SeriesComplete,
+    /// The series definition is unusable
+    InvalidSeries(String),
+    /// The results database could not be opened
+    NoResultsDb,
+    /// No multiplayer race is being hosted
+    NotHosting,
+    /// Reading or writing a file failed
+    Io(String),
+    /// A results database query failed
+    Database(String),
+    /// The simulation thread or a server lock is gone
+    Unavailable,
+}
+
+impl GameError {
+    /// Stable machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidConfig { .. } => "invalid_config",
+            Self::InvalidState { .. } => "invalid_state",
+            Self::InvalidTransition { .. } | Self::InvalidRaceTransition { .. } => "invalid_transition",
+            Self::NoRace => "no_race",
+            Self::NoRunners => "no_runners",
+            Self::NoPlayer => "no_player",
+            Self::NoReplay => "no_replay",
+            Self::NoRoster => "no_roster",
+            Self::UnknownAthlete(_) => "unknown_athlete",
+            Self::NoSeries => "no_series",
+            Self::SeriesComplete => "series_complete",
+            Self::InvalidSeries(_) => "invalid_series",
+            Self::NoResultsDb => "no_results_db",
+            Self::NotHosting => "not_hosting",
+            Self::Io(_) => "io",
+            Self::Database(_) => "database",
+            Self::Unavailable => "unavailable",
+        }
+    }
+
+    /// Shorthand for an `InvalidConfig` error
+    pub fn config(field: &'static str, reason: impl Into<String>) -> Self {
+        Self::InvalidConfig { field, reason: reason.into() }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidConfig { field, reason } => write!(f, "Invalid {}: {}", field, reason),
+            Self::InvalidState { command, state } => write!(f, "Can't {} while {:?}", command, state),
+            Self::InvalidTransition { from, to } => write!(f, "Can't go from {:?} to {:?}", from, to),
+            Self::InvalidRaceTransition { from, to } => write!(f, "Race can't go from {:?} to {:?}", from, to),
+            Self::NoRace => write!(f, "No race initialized"),
+            Self::NoRunners => write!(f, "Race has no runners"),
+            Self::NoPlayer => write!(f, "No player in the current race"),
+            Self::NoReplay => write!(f, "No replay recorded or loaded"),
+            Self::NoRoster => write!(f, "No roster loaded"),
+            Self::UnknownAthlete(bib) => write!(f, "No athlete with bib {} in roster", bib),
+            Self::NoSeries => write!(f, "No series in progress"),
+            Self::SeriesComplete => write!(f, "Series is complete"),
+            Self::InvalidSeries(reason) => write!(f, "{}", reason),
+            Self::NoResultsDb => write!(f, "No results database"),
+            Self::NotHosting => write!(f, "Not hosting a race"),
+            Self::Io(reason) => write!(f, "{}", reason),
+            Self::Database(reason) => write!(f, "Results database error: {}", reason),
+            Self::Unavailable => write!(f, "Game server unavailable"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl Serialize for GameError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("GameError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<io::Error> for GameError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for GameError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Database(e.to_string())
+    }
+}
@@ -0,0 +1,69 @@
+//! Events - What happened during a race
+//!
+//! `Race::update` logs a `RaceEvent` for everything clients react to
+//! (audio cues, commentary, overlays), so nobody has to diff snapshots.
+//! Times are race time in seconds.
+
+use serde::{Deserialize, Serialize};
+
+/// Something that happened during a race
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RaceEvent {
+    /// The gun went
+    RaceStart { time: f32 },
+    /// A new runner leads the race
+    LeadChange {
+        time: f32,
+        runner_id: u32,
+        /// Who led before
+        previous_id: u32,
+        /// Meters left for the new leader
+        to_go: f32,
+    },
+    /// One runner moved past another
+    Pass {
+        time: f32,
+        runner_id: u32,
+        passed_id: u32,
+        /// Place the passing runner moved up to
+        place: u32,
+        /// Meters left for the passing runner
+        to_go: f32,
+    },
+    /// A runner crossed the finish line with laps still to run
+    LapCompleted { time: f32, runner_id: u32, laps_to_go: u32 },
+    /// The leader started the last lap
+    BellLap { time: f32, runner_id: u32 },
+    /// A runner crossed a timing point (the finish included)
+    SplitCrossed {
+        time: f32,
+        runner_id: u32,
+        /// Timing point distance from the start
+        distance: f32,
+        /// Time since the runner's previous timing point
+        split: f32,
+        /// Order the runner crossed this timing point in
+        place: u32,
+    },
+    /// A runner finished
+    RunnerFinished { time: f32, runner_id: u32, position: u32 },
+    /// The last runner finished
+    RaceFinished { time: f32, winner_id: Option<u32> },
+}
+
+impl RaceEvent {
+    /// Race time the event happened at
+    pub fn time(&self) -> f32 {
+        match *self {
+            Self::RaceStart { time }
+            | Self::LeadChange { time, .. }
+            | Self::Pass { time, .. }
+            | Self::LapCompleted { time, .. }
+            | Self::BellLap { time, .. }
+            | Self::SplitCrossed { time, .. }
+            | Self::RunnerFinished { time, .. }
+            | Self::RaceFinished { time, .. } => time,
+        }
+    }
+}
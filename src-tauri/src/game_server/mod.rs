@@ -6,7 +6,24 @@
 pub mod runner;
 pub mod race;
 pub mod simulation;
+pub mod collision;
+pub mod commentary;
+pub mod drafting;
+pub mod energy;
+pub mod error;
+pub mod events;
+pub mod track;
+pub mod variability;
+pub mod net;
+pub mod pacing;
+pub mod replay;
+pub mod results;
+pub mod roster;
+pub mod runtime;
+pub mod series;
+pub mod state;
+pub mod wire;
 
 pub use runner::{Runner, RunnerState};
 pub use race::{Race, RaceConfig, RaceStatus};
-pub use simulation::{GameServer, GameState};
+pub use simulation::{GameServer, GameState, TickResult};
@@ -0,0 +1,377 @@
+//! Net - Local multiplayer host over WebSocket
+//!
+//! Hosts the race of a `SimulationThread` on a WebSocket endpoint. Clients
+//! join as players, send inputs, and receive `RaceSnapshot`s at the
+//! server's tick rate. Every message is a JSON text frame tagged by `type`.
+//!
+//! The host plays as an ordinary client, so nobody's browser is the
+//! authority. The race runs on the same simulation thread as a local one,
+//! so it goes through the same state machine and is recorded the same way.
+
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+use crate::game_server::error::GameError;
+use crate::game_server::race::{PlayerConfig, RaceResult, RaceSnapshot};
+use crate::game_server::runner::PlayerInput;
+use crate::game_server::runtime::{SimEvent, SimulationThread};
+
+/// Message from a client to the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Join the waiting race as a player, optionally with their own finish
+    /// time for the distance (see `PlayerConfig::target_time`)
+    Join {
+        name: String,
+        #[serde(default)]
+        target_time: Option<f32>,
+    },
+    /// Latest player input
+    Input { desired_speed: f32, lane_change: f32 },
+    /// Leave the race (the runner is handed to the AI)
+    Leave,
+}
+
+/// Message from the host to a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Join accepted; this client controls `runner_id`
+    Joined { runner_id: u32 },
+    /// Race state after a server tick
+    Snapshot { snapshot: RaceSnapshot },
+    /// Final results once every runner has finished
+    Results { results: Vec<RaceResult> },
+    /// Request rejected
+    Error { message: String },
+}
+
+/// Outgoing message queues of all connected clients
+type Clients = Arc<Mutex<Vec<Sender<Arc<str>>>>>;
+
+/// Result of serving a client connection
+type ClientResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// WebSocket host for the race on a simulation thread
+pub struct NetServer {
+    sim: Arc<SimulationThread>,
+    /// Event subscription that broadcasts snapshots and results
+    subscription: u32,
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl NetServer {
+    /// How long a client thread waits for input before flushing outgoing messages
+    const CLIENT_POLL: Duration = Duration::from_millis(2);
+    /// How often the accept loop checks for new connections
+    const ACCEPT_POLL: Duration = Duration::from_millis(10);
+    /// How long a new connection gets to complete the WebSocket handshake
+    const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Bind to `addr` and start accepting clients into the race on `sim`.
+    /// The caller sets up the race (with an open lobby) and starts it.
+    pub fn start(sim: Arc<SimulationThread>, addr: impl ToSocketAddrs) -> Result<Self, GameError> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+
+        let subscription = {
+            let clients = clients.clone();
+            sim.subscribe_events(move |event| {
+                match event {
+                    SimEvent::Snapshot(result) => {
+                        Self::broadcast(&clients, &ServerMessage::Snapshot { snapshot: result.snapshot.clone() })
+                    }
+                    SimEvent::Results(results) => {
+                        Self::broadcast(&clients, &ServerMessage::Results { results: results.clone() })
+                    }
+                    _ => {}
+                }
+                true
+            })?
+        };
+
+        let accept_thread = {
+            let (sim, running) = (sim.clone(), running.clone());
+            thread::spawn(move || Self::accept_loop(listener, sim, clients, running))
+        };
+
+        log::info!("Multiplayer host listening on ws://{}", addr);
+
+        Ok(Self {
+            sim,
+            subscription,
+            addr,
+            running,
+            threads: vec![accept_thread],
+        })
+    }
+
+    /// Address the host is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stop accepting clients and disconnect everyone. The race itself
+    /// carries on with the AI running for the players.
+    pub fn stop(&mut self) {
+        if self.running.swap(false, Ordering::Relaxed) {
+            let _ = self.sim.unsubscribe_events(self.subscription);
+        }
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+    }
+
+    /// Accept connections until stopped, one thread per client. Client
+    /// threads are joined before returning.
+    fn accept_loop(listener: TcpListener, sim: Arc<SimulationThread>, clients: Clients, running: Arc<AtomicBool>) {
+        let mut client_threads: Vec<JoinHandle<()>> = Vec::new();
+
+        while running.load(Ordering::Relaxed) {
+            client_threads.retain(|handle| !handle.is_finished());
+
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(Self::ACCEPT_POLL);
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("Multiplayer accept failed: {}", e);
+                    continue;
+                }
+            };
+
+            let (tx, rx) = mpsc::channel();
+            if let Ok(mut clients) = clients.lock() {
+                clients.push(tx);
+            }
+
+            let (sim, running) = (sim.clone(), running.clone());
+            client_threads.push(thread::spawn(move || {
+                if let Err(e) = Self::serve_client(stream, sim, rx, running) {
+                    log::warn!("Multiplayer client dropped: {}", e);
+                }
+            }));
+        }
+
+        for handle in client_threads {
+            let _ = handle.join();
+        }
+    }
+
+    /// Queue a message for every client, dropping clients that have gone away
+    fn broadcast(clients: &Clients, message: &ServerMessage) {
+        let Ok(text) = serde_json::to_string(message) else { return };
+        let text: Arc<str> = text.into();
+
+        if let Ok(mut clients) = clients.lock() {
+            clients.retain(|tx| tx.send(text.clone()).is_ok());
+        }
+    }
+
+    /// Handle one client connection until it closes or the host stops
+    fn serve_client(
+        stream: TcpStream,
+        sim: Arc<SimulationThread>,
+        outbox: Receiver<Arc<str>>,
+        running: Arc<AtomicBool>,
+    ) -> ClientResult {
+        stream.set_nonblocking(false)?;
+        // A client that never finishes the handshake mustn't hold up `stop`
+        stream.set_read_timeout(Some(Self::HANDSHAKE_TIMEOUT))?;
+        let mut ws = tungstenite::accept(stream).map_err(|e| e.to_string())?;
+        ws.get_ref().set_read_timeout(Some(Self::CLIENT_POLL))?;
+
+        let mut runner_id = None;
+        let outcome = Self::client_loop(&mut ws, &sim, &outbox, &running, &mut runner_id);
+
+        // Leave the runner to the AI so the race can still finish
+        if let Some(id) = runner_id {
+            let _ = sim.call(move |server| server.release_player(id));
+        }
+
+        let _ = ws.close(None);
+        outcome
+    }
+
+    fn client_loop(
+        ws: &mut WebSocket<TcpStream>,
+        sim: &SimulationThread,
+        outbox: &Receiver<Arc<str>>,
+        running: &AtomicBool,
+        runner_id: &mut Option<u32>,
+    ) -> ClientResult {
+        while running.load(Ordering::Relaxed) {
+            match ws.read() {
+                Ok(Message::Text(text)) => {
+                    let reply = match serde_json::from_str(&text) {
+                        // The runner is released once the loop exits
+                        Ok(ClientMessage::Leave) => return Ok(()),
+                        Ok(message) => Self::handle_message(message, sim, runner_id),
+                        Err(e) => Some(ServerMessage::Error { message: format!("Invalid message: {}", e) }),
+                    };
+                    if let Some(reply) = reply {
+                        ws.send(Message::Text(serde_json::to_string(&reply).unwrap_or_default()))?;
+                    }
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+                Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+
+            loop {
+                match outbox.try_recv() {
+                    Ok(text) => ws.send(Message::Text(text.to_string()))?,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply a client message, returning a direct reply if there is one
+    fn handle_message(
+        message: ClientMessage,
+        sim: &SimulationThread,
+        runner_id: &mut Option<u32>,
+    ) -> Option<ServerMessage> {
+        let unavailable = || Some(ServerMessage::Error { message: GameError::Unavailable.to_string() });
+
+        match message {
+            ClientMessage::Join { .. } if runner_id.is_some() => {
+                Some(ServerMessage::Error { message: "Already joined".to_string() })
+            }
+            ClientMessage::Join { name, target_time } => {
+                let player = PlayerConfig { name, target_time };
+                if let Err(e) = player.validate() {
+                    return Some(ServerMessage::Error { message: e.to_string() });
+                }
+                match sim.call(move |server| server.add_player(&player)) {
+                    Ok(Some(id)) => {
+                        *runner_id = Some(id);
+                        Some(ServerMessage::Joined { runner_id: id })
+                    }
+                    Ok(None) => Some(ServerMessage::Error { message: "No race is open for joining".to_string() }),
+                    Err(_) => unavailable(),
+                }
+            }
+            ClientMessage::Input { desired_speed, lane_change } => {
+                let Some(id) = *runner_id else {
+                    return Some(ServerMessage::Error { message: "Join the race before sending input".to_string() });
+                };
+                let input = PlayerInput { desired_speed, lane_change };
+                let Ok(accepted) = sim.call(move |server| server.set_runner_input(id, input)) else {
+                    return unavailable();
+                };
+                (!accepted).then(|| ServerMessage::Error { message: "Join the race before sending input".to_string() })
+            }
+            // Handled by the client loop, which closes the connection
+            ClientMessage::Leave => None,
+        }
+    }
+}
+
+impl Drop for NetServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_server::race::RaceConfig;
+    use crate::game_server::runtime::SimCommand;
+    use crate::game_server::simulation::GameServer;
+
+    /// Long enough for the countdown and a short race
+    const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+    fn connect(addr: SocketAddr) -> WebSocket<TcpStream> {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+        tungstenite::client(format!("ws://{}", addr), stream).unwrap().0
+    }
+
+    fn send(ws: &mut WebSocket<TcpStream>, message: &ClientMessage) {
+        ws.send(Message::Text(serde_json::to_string(message).unwrap())).unwrap();
+    }
+
+    /// Read messages until `f` picks one out
+    fn read_until<T>(ws: &mut WebSocket<TcpStream>, mut f: impl FnMut(ServerMessage) -> Option<T>) -> T {
+        loop {
+            if let Message::Text(text) = ws.read().unwrap() {
+                if let Some(found) = f(serde_json::from_str(&text).unwrap()) {
+                    return found;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_clients_race_to_results() {
+        let sim = Arc::new(SimulationThread::spawn(GameServer::new(), |_| {}));
+        let config = RaceConfig {
+            runner_count: 0,
+            distance: 20.0,
+            time_scale: 1.0,
+            seed: Some(1),
+            lobby: true,
+            ..Default::default()
+        };
+        sim.call(move |server| server.init_race(config)).unwrap().unwrap();
+
+        let mut host = NetServer::start(sim.clone(), "127.0.0.1:0").unwrap();
+        let mut clients: Vec<_> = (0..2).map(|_| connect(host.local_addr())).collect();
+
+        let mut ids = Vec::new();
+        for (i, ws) in clients.iter_mut().enumerate() {
+            send(ws, &ClientMessage::Join { name: format!("Player {}", i), target_time: None });
+            ids.push(read_until(ws, |message| match message {
+                ServerMessage::Joined { runner_id } => Some(runner_id),
+                ServerMessage::Error { message } => panic!("join rejected: {}", message),
+                _ => None,
+            }));
+        }
+        assert_ne!(ids[0], ids[1]);
+        ids.sort_unstable();
+
+        sim.control(SimCommand::Start).unwrap();
+        for ws in &mut clients {
+            send(ws, &ClientMessage::Input { desired_speed: 7.0, lane_change: 0.0 });
+        }
+
+        for ws in &mut clients {
+            let results = read_until(ws, |message| match message {
+                ServerMessage::Results { results } => Some(results),
+                ServerMessage::Error { message } => panic!("input rejected: {}", message),
+                _ => None,
+            });
+            let mut finishers: Vec<_> = results.iter().map(|r| r.runner_id).collect();
+            finishers.sort_unstable();
+            assert_eq!(finishers, ids);
+            send(ws, &ClientMessage::Leave);
+        }
+
+        host.stop();
+    }
+}
@@ -0,0 +1,125 @@
+//! Pacing - Race strategy archetypes for AI runners
+//!
+//! A strategy reshapes how a runner spreads its finish time over the
+//! splits (the total stays the same, so ability is unchanged) and adds a
+//! little in-race behaviour on top of the planned pace.
+
+use std::collections::BTreeMap;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::game_server::runner::Runner;
+
+/// How a runner distributes its effort over the race
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum PacingStrategy {
+    /// Same pace throughout
+    #[default]
+    Even,
+    /// Starts conservatively and speeds up
+    NegativeSplit,
+    /// Starts fast and fades
+    PositiveSplit,
+    /// Goes straight to the front, then hangs on
+    FrontRunner,
+    /// Sits in the pack and saves everything for a late kick
+    SitAndKick,
+    /// Even splits broken up by repeated surges
+    Surger,
+}
+
+/// Relative weight of each strategy in a field
+pub type PacingMix = BTreeMap<PacingStrategy, f32>;
+
+impl PacingStrategy {
+    /// Pace swing of the split strategies (fraction of even pace)
+    const SPLIT_SWING: f32 = 0.03;
+    /// Front runner: share of the race run hard, and how much faster
+    const FRONT_SHARE: f32 = 0.3;
+    const FRONT_PUSH: f32 = 0.06;
+    /// Sit-and-kick: share of the race saved for the kick, and how much faster
+    const KICK_SHARE: f32 = 0.2;
+    const KICK_PUSH: f32 = 0.1;
+    /// Sit-and-kick: easing off in a slipstream to sit in rather than press
+    /// on, which cancels the draft's free speed exactly
+    const SIT_IN: f32 = 1.0 / Runner::DRAFT_SPEED_BONUS;
+    /// Surger: distance between surges, their length and speed boost
+    const SURGE_SPACING: f32 = 300.0;
+    const SURGE_LENGTH: f32 = 60.0;
+    const SURGE_BOOST: f32 = 1.06;
+    /// Front runners get out quicker
+    const FRONT_ACCELERATION: f32 = 1.5;
+
+    /// Default field: mostly even pacers with some of every type
+    pub fn default_mix() -> PacingMix {
+        PacingMix::from([
+            (Self::Even, 0.4),
+            (Self::NegativeSplit, 0.15),
+            (Self::PositiveSplit, 0.15),
+            (Self::FrontRunner, 0.1),
+            (Self::SitAndKick, 0.1),
+            (Self::Surger, 0.1),
+        ])
+    }
+
+    /// Pick a strategy with probability proportional to its weight
+    pub fn pick<R: Rng + ?Sized>(mix: &PacingMix, rng: &mut R) -> Self {
+        let total: f32 = mix.values().map(|w| w.max(0.0)).sum();
+        if total <= 0.0 {
+            return Self::Even;
+        }
+
+        let mut roll = rng.gen::<f32>() * total;
+        for (&strategy, &weight) in mix {
+            roll -= weight.max(0.0);
+            if roll < 0.0 {
+                return strategy;
+            }
+        }
+        // Float rounding left a sliver at the end
+        mix.keys().next_back().copied().unwrap_or_default()
+    }
+
+    /// Relative time spent on a segment centred at `fraction` (0-1) of the
+    /// race. Splits are normalised afterwards, so only the shape matters.
+    pub fn segment_weight(&self, fraction: f32) -> f32 {
+        match self {
+            Self::Even | Self::Surger => 1.0,
+            Self::NegativeSplit => 1.0 + Self::SPLIT_SWING * (1.0 - 2.0 * fraction),
+            Self::PositiveSplit => 1.0 - Self::SPLIT_SWING * (1.0 - 2.0 * fraction),
+            Self::FrontRunner if fraction < Self::FRONT_SHARE => 1.0 - Self::FRONT_PUSH,
+            Self::FrontRunner => 1.0 + Self::SPLIT_SWING * (fraction - Self::FRONT_SHARE) / (1.0 - Self::FRONT_SHARE),
+            Self::SitAndKick if fraction > 1.0 - Self::KICK_SHARE => 1.0 - Self::KICK_PUSH,
+            Self::SitAndKick => 1.0,
+        }
+    }
+
+    /// In-race multiplier on planned speed at `progress` meters.
+    /// `surge_offset` staggers surges between runners.
+    pub fn speed_multiplier(&self, progress: f32, surge_offset: f32, drafting: bool) -> f32 {
+        match self {
+            Self::SitAndKick if drafting => Self::SIT_IN,
+            Self::Surger if (progress + surge_offset).rem_euclid(Self::SURGE_SPACING) < Self::SURGE_LENGTH => {
+                Self::SURGE_BOOST
+            }
+            // Ease off between surges so the average pace still matches the splits
+            Self::Surger => {
+                let surge_share = Self::SURGE_LENGTH / Self::SURGE_SPACING;
+                (1.0 - surge_share) / (1.0 - surge_share / Self::SURGE_BOOST)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Multiplier on acceleration
+    pub fn acceleration_multiplier(&self) -> f32 {
+        match self {
+            Self::FrontRunner => Self::FRONT_ACCELERATION,
+            _ => 1.0,
+        }
+    }
+
+    /// Random surge offset for a new runner (meters)
+    pub fn random_surge_offset<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+        rng.gen::<f32>() * Self::SURGE_SPACING
+    }
+}
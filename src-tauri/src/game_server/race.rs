@@ -2,8 +2,25 @@
 //!
 //! Handles race setup, timing, and finish detection.
 
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use crate::game_server::runner::{RunnerState, Runner, RunnerSnapshot};
+use crate::game_server::collision::{Collision, SpatialHashGrid};
+use crate::game_server::drafting::Drafting;
+use crate::game_server::energy::{EnergySystem, EnergyType};
+use crate::game_server::error::GameError;
+use crate::game_server::events::RaceEvent;
+use crate::game_server::pacing::{PacingMix, PacingStrategy};
+use crate::game_server::results::ResultFlag;
+use crate::game_server::roster::{Athlete, Roster};
+use crate::game_server::runner::{
+    LaneAssignment, PlayerInput, RunnerControl, RunnerState, Runner, RunnerSnapshot, SplitInterval, SplitTimes,
+};
+use crate::game_server::track;
+use crate::game_server::variability::Variability;
+
+pub use crate::game_server::state::RaceStatus;
 
 /// Race configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,10 +29,231 @@ pub struct RaceConfig {
     pub distance: f32,
     /// Number of runners
     pub runner_count: u32,
-    /// Time scale factor (higher = faster simulation)
+    /// Slow-motion factor (1 = real time): runners and the race clock both
+    /// run `time_scale` times slower than real time. Recorded times are the
+    /// same at any setting; only the number of steps a race takes changes.
     pub time_scale: f32,
     /// Starting formation spread
     pub formation_spread: f32,
+    /// RNG seed (random if not set). Equal seeds produce identical races.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Energy model for runners (chosen from the distance if not set)
+    #[serde(default)]
+    pub energy_type: Option<EnergyType>,
+    /// Distance between pacing splits (chosen from the distance if not set)
+    #[serde(default)]
+    pub split_interval: Option<SplitInterval>,
+    /// Include world x/z positions in runner snapshots
+    #[serde(default)]
+    pub world_positions: bool,
+    /// How runners line up at the start
+    #[serde(default)]
+    pub start_formation: StartFormation,
+    /// Human player joining the AI field (in addition to `runner_count`)
+    #[serde(default)]
+    pub player: Option<PlayerConfig>,
+    /// Where actual split times are recorded (the split interval if not set)
+    #[serde(default)]
+    pub timing_points: Option<TimingPoints>,
+    /// Relative weight of each pacing strategy among AI runners
+    /// (`PacingStrategy::default_mix` if not set)
+    #[serde(default)]
+    pub pacing_mix: Option<PacingMix>,
+    /// How often and how hard AI runners surge and have bad patches
+    /// (1.0 if not set, 0 turns it off)
+    #[serde(default)]
+    pub variability: Option<f32>,
+    /// Athletes to draw the field from; anonymous runners fill any gap
+    #[serde(default)]
+    pub roster: Option<Roster>,
+    /// Bibs of the roster athletes to enter (a random draw if empty)
+    #[serde(default)]
+    pub entrants: Vec<u32>,
+    /// Players join over the network after the race is set up, so it may
+    /// start out with nobody in it
+    #[serde(default)]
+    pub lobby: bool,
+}
+
+/// Human player entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerConfig {
+    pub name: String,
+    /// The player's own finish time for the distance (seconds). Fatigue is
+    /// measured against this pace, so it sets how fast the player can run
+    /// before fading. Defaults to a 20:00 5K scaled to the race distance.
+    #[serde(default)]
+    pub target_time: Option<f32>,
+}
+
+impl PlayerConfig {
+    /// Check the name and target time
+    pub fn validate(&self) -> Result<(), GameError> {
+        if self.name.trim().is_empty() {
+            return Err(GameError::config("player", "name must not be empty"));
+        }
+        if let Some(time) = self.target_time.filter(|t| !(t.is_finite() && *t > 0.0)) {
+            return Err(GameError::config(
+                "player",
+                format!("target time must be greater than 0, got {}", time),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Start formation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StartFormation {
+    /// Rows of 10 behind a single line, free to cut in from the gun
+    #[default]
+    Waterfall,
+    /// Inner group in lanes 1-4, outer group staggered from lane 5 until the break
+    Alley,
+    /// One runner per lane with lane staggers (200m/400m lane races)
+    LaneStagger,
+}
+
+/// Where actual split times are recorded. The finish is always a timing point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TimingPoints {
+    /// Every lap, kilometre, mile or custom spacing
+    Every(SplitInterval),
+    /// Timing mats at these distances from the start
+    Mats(Vec<f32>),
+}
+
+impl RaceConfig {
+    /// Largest AI field a race can be configured with
+    pub const MAX_RUNNERS: u32 = 1000;
+
+    /// Check every value is usable before a race is built from it
+    pub fn validate(&self) -> Result<(), GameError> {
+        if !(self.distance.is_finite() && self.distance > 0.0) {
+            return Err(GameError::config(
+                "distance",
+                format!("must be a positive number of meters, got {}", self.distance),
+            ));
+        }
+        if self.field_size() == 0 && !self.lobby {
+            return Err(GameError::config(
+                "runner_count",
+                "must be at least 1 without a player, roster entrants or an open lobby",
+            ));
+        }
+        if self.runner_count > Self::MAX_RUNNERS {
+            return Err(GameError::config(
+                "runner_count",
+                format!("must be at most {}, got {}", Self::MAX_RUNNERS, self.runner_count),
+            ));
+        }
+        if !(self.time_scale.is_finite() && self.time_scale > 0.0) {
+            return Err(GameError::config(
+                "time_scale",
+                format!("must be greater than 0, got {}", self.time_scale),
+            ));
+        }
+        if !(self.formation_spread.is_finite() && self.formation_spread >= 0.0) {
+            return Err(GameError::config(
+                "formation_spread",
+                format!("must be 0 or more, got {}", self.formation_spread),
+            ));
+        }
+        if let Some(SplitInterval::Custom(meters)) = self.split_interval {
+            Self::validate_interval("split_interval", meters)?;
+        }
+        match &self.timing_points {
+            Some(TimingPoints::Every(SplitInterval::Custom(meters))) => {
+                Self::validate_interval("timing_points", *meters)?
+            }
+            Some(TimingPoints::Mats(mats)) => {
+                if let Some(mat) = mats.iter().find(|d| !d.is_finite()) {
+                    return Err(GameError::config(
+                        "timing_points",
+                        format!("mat distance {} is not a number", mat),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        let bad_weight = self.pacing_mix.iter().flatten().find(|(_, w)| !(w.is_finite() && **w >= 0.0));
+        if let Some((strategy, weight)) = bad_weight {
+            return Err(GameError::config(
+                "pacing_mix",
+                format!("weight for {:?} must be 0 or more, got {}", strategy, weight),
+            ));
+        }
+        if let Some(variability) = self.variability.filter(|v| !(v.is_finite() && *v >= 0.0)) {
+            return Err(GameError::config("variability", format!("must be 0 or more, got {}", variability)));
+        }
+        if let Some(player) = &self.player {
+            player.validate()?;
+        }
+        if let Some(roster) = &self.roster {
+            roster.validate().map_err(|reason| GameError::config("roster", reason))?;
+        }
+        if !self.entrants.is_empty() {
+            let roster = self.roster.as_ref().ok_or(GameError::NoRoster)?;
+            if let Some(&bib) = self.entrants.iter().find(|&&bib| roster.get(bib).is_none()) {
+                return Err(GameError::UnknownAthlete(bib));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_interval(field: &'static str, meters: f32) -> Result<(), GameError> {
+        if meters.is_finite() && meters >= 1.0 {
+            Ok(())
+        } else {
+            Err(GameError::config(field, format!("custom interval must be at least 1 m, got {}", meters)))
+        }
+    }
+
+    /// Number of runners the race will line up with, counting roster
+    /// entrants and the player
+    pub fn field_size(&self) -> usize {
+        (self.runner_count as usize).max(self.entrants.len()) + self.player.is_some() as usize
+    }
+
+    /// Energy model in effect (explicit, or matching the race distance)
+    pub fn energy_type(&self) -> EnergyType {
+        self.energy_type.unwrap_or_else(|| EnergyType::for_distance(self.distance))
+    }
+
+    /// Split interval in effect (explicit, or matching the race distance)
+    pub fn split_interval(&self) -> SplitInterval {
+        self.split_interval.unwrap_or_else(|| SplitInterval::for_distance(self.distance))
+    }
+
+    /// Pacing strategy mix in effect
+    pub fn pacing_mix(&self) -> PacingMix {
+        self.pacing_mix.clone().unwrap_or_else(PacingStrategy::default_mix)
+    }
+
+    /// In-race variability intensity in effect
+    pub fn variability(&self) -> f32 {
+        self.variability.unwrap_or(1.0).max(0.0)
+    }
+
+    /// Timing point distances in effect, ascending and ending at the finish
+    pub fn timing_distances(&self) -> Vec<f32> {
+        match &self.timing_points {
+            None => SplitTimes::split_distances(self.distance, self.split_interval().meters()),
+            Some(TimingPoints::Every(interval)) => SplitTimes::split_distances(self.distance, interval.meters()),
+            Some(TimingPoints::Mats(mats)) => {
+                let mut distances: Vec<f32> = mats
+                    .iter()
+                    .copied()
+                    .filter(|&d| d > 0.0 && d < self.distance)
+                    .collect();
+                distances.sort_by(f32::total_cmp);
+                distances.dedup();
+                distances.push(self.distance);
+                distances
+            }
+        }
+    }
 }
 
 impl Default for RaceConfig {
@@ -25,17 +263,61 @@ impl Default for RaceConfig {
             runner_count: 100,
             time_scale: 10.0,
             formation_spread: 3.0,
+            seed: None,
+            energy_type: None,
+            split_interval: None,
+            world_positions: false,
+            start_formation: StartFormation::default(),
+            player: None,
+            timing_points: None,
+            pacing_mix: None,
+            variability: None,
+            roster: None,
+            entrants: Vec::new(),
+            lobby: false,
         }
     }
 }
 
-/// Race status
+/// Actual time recorded at a timing point
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RecordedSplit {
+    /// Timing point distance from the start
+    pub distance: f32,
+    /// Race time when the runner crossed it
+    pub time: f32,
+    /// Time since the previous timing point
+    pub split: f32,
+}
+
+/// Recorded splits for one runner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerSplits {
+    pub runner_id: u32,
+    pub splits: Vec<RecordedSplit>,
+}
+
+/// A runner's live place in the race and how far they are off the pace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Placing {
+    /// Current place (1 = leading)
+    pub place: u32,
+    /// Distance behind the leader (m)
+    pub gap: f32,
+    /// Time behind the leader (race seconds)
+    pub gap_time: f32,
+    /// Distance behind the runner one place ahead (m)
+    pub interval: f32,
+    /// Time behind the runner one place ahead (race seconds)
+    pub interval_time: f32,
+}
+
+/// A runner whose place changed
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum RaceStatus {
-    NotStarted,
-    Countdown,
-    Racing,
-    Finished,
+pub struct PlaceChange {
+    pub runner_id: u32,
+    pub from: u32,
+    pub to: u32,
 }
 
 /// Race timing and results
@@ -45,6 +327,31 @@ pub struct RaceResult {
     pub runner_name: String,
     pub finish_time: f32,
     pub position: u32,
+    /// Actual time at every timing point
+    #[serde(default)]
+    pub splits: Vec<RecordedSplit>,
+    /// Pacing strategy the runner raced with
+    #[serde(default)]
+    pub strategy: PacingStrategy,
+    /// Roster bib, if the runner is a roster athlete
+    #[serde(default)]
+    pub bib: Option<u32>,
+    /// Whether a human was controlling the runner at the finish
+    #[serde(default)]
+    pub player: bool,
+    /// PB/SB/record flags, set when the race is stored in the results database
+    #[serde(default)]
+    pub flags: Vec<ResultFlag>,
+}
+
+/// Who is running in a race
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entrant {
+    pub runner_id: u32,
+    pub name: String,
+    pub strategy: PacingStrategy,
+    /// Roster details, if the runner is a roster athlete
+    pub athlete: Option<Athlete>,
 }
 
 /// Complete race state
@@ -62,11 +369,48 @@ pub struct Race {
     pub countdown: f32,
     /// Finish order (runner IDs)
     pub finish_order: Vec<RaceResult>,
+    /// Seed the race RNG was created from
+    pub seed: u64,
+    /// Distances where actual split times are recorded
+    pub timing_points: Vec<f32>,
+    /// Race RNG - every random decision in the race draws from this
+    #[serde(skip, default = "Race::entropy_rng")]
+    rng: StdRng,
+    /// Reusable spatial hash for collision and drafting passes
+    #[serde(skip)]
+    collision_grid: SpatialHashGrid,
+    /// Events logged since they were last taken
+    #[serde(skip)]
+    events: Vec<RaceEvent>,
+    /// Runner indices in running order, leader first
+    #[serde(skip)]
+    order: Vec<usize>,
+    /// Finishing position of each runner by index (0 until they finish)
+    #[serde(skip)]
+    finish_places: Vec<u32>,
+    /// Live placing of each runner by index (empty until the gun)
+    #[serde(skip)]
+    placings: Vec<Placing>,
+    /// Places as of the last `take_place_changes`, by runner index
+    #[serde(skip)]
+    reported_places: Vec<u32>,
+    /// Race time the front of the race first reached each whole meter
+    #[serde(skip)]
+    front_times: Vec<f32>,
+    /// How many runners have crossed each timing point
+    #[serde(skip)]
+    split_counts: Vec<u32>,
+    /// Whether the leader has started the last lap
+    #[serde(skip)]
+    bell_rung: bool,
 }
 
 impl Race {
     /// Create a new race with the given configuration
     pub fn new(config: RaceConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let timing_points = config.timing_distances();
+
         Self {
             config,
             status: RaceStatus::NotStarted,
@@ -74,36 +418,147 @@ impl Race {
             elapsed_time: 0.0,
             countdown: 3.0,
             finish_order: Vec::new(),
+            seed,
+            timing_points,
+            rng: StdRng::seed_from_u64(seed),
+            collision_grid: SpatialHashGrid::default(),
+            events: Vec::new(),
+            order: Vec::new(),
+            finish_places: Vec::new(),
+            placings: Vec::new(),
+            reported_places: Vec::new(),
+            front_times: Vec::new(),
+            split_counts: Vec::new(),
+            bell_rung: false,
         }
     }
 
-    /// Generate runners with realistic 5K finish times
+    /// Fallback RNG for races restored without their seed
+    fn entropy_rng() -> StdRng {
+        StdRng::from_entropy()
+    }
+
+    /// Generate runners with realistic finish times for the race distance
     pub fn generate_runners(&mut self) {
         self.runners.clear();
 
-        // Generate finish times with realistic distribution
-        // Elite: 13-14 min, Good: 15-18 min, Average: 19-25 min, Slow: 26-35 min
-        let finish_times = Self::generate_finish_times(self.config.runner_count as usize);
+        let distance = self.config.distance;
+        let athletes = self.draw_athletes();
+        let count = (self.config.runner_count as usize).max(athletes.len());
+        let finish_times = Self::generate_finish_times(count, distance, &mut self.rng);
+
+        let energy_type = self.config.energy_type();
+        let split_interval = self.config.split_interval();
+        let pacing_mix = self.config.pacing_mix();
 
         for (i, finish_time) in finish_times.into_iter().enumerate() {
-            let name = format!("Runner {}", i + 1);
-            self.runners.push(RunnerState::new(i as u32, name, finish_time));
+            // Roster athletes take the first slots, anonymous runners fill the rest
+            let athlete = athletes.get(i);
+            let name = athlete.map_or_else(|| format!("Runner {}", i + 1), |a| a.name.clone());
+            let finish_time = athlete
+                .and_then(|a| a.race_time(distance, &mut self.rng))
+                .unwrap_or(finish_time);
+            let mix = athlete.and_then(|a| a.pacing.as_ref()).unwrap_or(&pacing_mix);
+
+            let strategy = PacingStrategy::pick(mix, &mut self.rng);
+            let split_times = SplitTimes::from_finish_time(finish_time, distance, split_interval, strategy, &mut self.rng);
+            let mut runner = RunnerState::new(i as u32, name, split_times, strategy, &mut self.rng);
+            runner.energy = EnergySystem::new(energy_type);
+            runner.bib = athlete.map(|a| a.bib);
+            self.runners.push(runner);
+        }
+
+        // Human player joins at the back of the field
+        if let Some(player) = self.config.player.clone() {
+            self.add_player(&player);
         }
     }
 
-    /// Generate realistic 5K finish times
-    fn generate_finish_times(count: usize) -> Vec<f32> {
+    /// Roster athletes entered in this race: the configured entrants, or a
+    /// random draw of up to `runner_count`
+    fn draw_athletes(&mut self) -> Vec<Athlete> {
+        let Some(roster) = &self.config.roster else {
+            return Vec::new();
+        };
+
+        if !self.config.entrants.is_empty() {
+            return self
+                .config
+                .entrants
+                .iter()
+                .filter_map(|&bib| roster.get(bib).cloned())
+                .collect();
+        }
+
+        let count = (self.config.runner_count as usize).min(roster.athletes.len());
+        roster.athletes.choose_multiple(&mut self.rng, count).cloned().collect()
+    }
+
+    /// Who is running, with roster details where available
+    pub fn get_entrants(&self) -> Vec<Entrant> {
+        self.runners
+            .iter()
+            .map(|runner| Entrant {
+                runner_id: runner.id,
+                name: runner.name.clone(),
+                strategy: runner.strategy,
+                athlete: runner
+                    .bib
+                    .and_then(|bib| self.config.roster.as_ref()?.get(bib).cloned()),
+            })
+            .collect()
+    }
+
+    /// Add a human-controlled runner at the back of the field, returning its ID.
+    /// Call `setup_starting_positions` afterwards to give it a start slot, or
+    /// use `join_player` once the field is lined up.
+    pub fn add_player(&mut self, player: &PlayerConfig) -> u32 {
+        let distance = self.config.distance;
+        let id = self.runners.iter().map(|r| r.id + 1).max().unwrap_or(0);
+        let finish_time = player
+            .target_time
+            .unwrap_or_else(|| Self::PLAYER_REFERENCE_5K * Self::distance_factor(distance));
+        let split_times = SplitTimes::from_finish_time(
+            finish_time,
+            distance,
+            self.config.split_interval(),
+            PacingStrategy::Even,
+            &mut self.rng,
+        );
+
+        let mut runner = RunnerState::new(id, player.name.clone(), split_times, PacingStrategy::Even, &mut self.rng);
+        runner.control = RunnerControl::Player(PlayerInput::default());
+        runner.energy = EnergySystem::new(self.config.energy_type());
+        self.runners.push(runner);
+        id
+    }
+
+    /// Default player reference time for a 5K (seconds)
+    const PLAYER_REFERENCE_5K: f32 = 1200.0;
+
+    /// Riegel fatigue exponent used to scale times between distances
+    pub(crate) const RIEGEL_EXPONENT: f32 = 1.06;
+
+    /// Time multiplier from a 5K to the given distance
+    fn distance_factor(distance: f32) -> f32 {
+        (distance / 5000.0).powf(Self::RIEGEL_EXPONENT)
+    }
+
+    /// Generate realistic finish times for a distance
+    fn generate_finish_times<R: Rng + ?Sized>(count: usize, distance: f32, rng: &mut R) -> Vec<f32> {
         let mut times = Vec::with_capacity(count);
+        let distance_factor = Self::distance_factor(distance);
 
         for i in 0..count {
-            // Create a bell curve distribution around 20 minutes
+            // Create a bell curve distribution around a 20 minute 5K
+            // Elite: 13-14 min, Good: 15-18 min, Average: 19-25 min, Slow: 26-35 min
             let base = match i % 10 {
-                0 => 780.0 + rand::random::<f32>() * 60.0,   // 13:00-14:00 (elite)
-                1..=2 => 900.0 + rand::random::<f32>() * 180.0, // 15:00-18:00 (good)
-                3..=6 => 1140.0 + rand::random::<f32>() * 360.0, // 19:00-25:00 (average)
-                _ => 1560.0 + rand::random::<f32>() * 540.0,  // 26:00-35:00 (slow)
+                0 => 780.0 + rng.gen::<f32>() * 60.0,   // 13:00-14:00 (elite)
+                1..=2 => 900.0 + rng.gen::<f32>() * 180.0, // 15:00-18:00 (good)
+                3..=6 => 1140.0 + rng.gen::<f32>() * 360.0, // 19:00-25:00 (average)
+                _ => 1560.0 + rng.gen::<f32>() * 540.0,  // 26:00-35:00 (slow)
             };
-            times.push(base);
+            times.push(base * distance_factor);
         }
 
         // Sort by finish time (fastest first)
@@ -111,28 +566,140 @@ impl Race {
         times
     }
 
-    /// Set up starting positions in a formation
+    /// Runners per row in waterfall and alley starts
+    const ROW_WIDTH: usize = 10;
+    /// Share of the field starting in the outer alley
+    const ALLEY_OUTER_SHARE: f32 = 0.35;
+    /// First lane of the outer alley
+    const ALLEY_OUTER_LANE: u32 = 5;
+    /// Distance run in lanes before breaking (end of the first bend)
+    const LANE_BREAK_DISTANCE: f32 = 100.0;
+
+    /// Set up starting positions in the configured formation
     pub fn setup_starting_positions(&mut self) {
-        let spread = self.config.formation_spread;
+        let count = self.runners.len();
+        let inner_count = count - Self::alley_outer_count(count);
 
-        for (i, runner) in self.runners.iter_mut().enumerate() {
-            // Stagger runners in rows
-            let row = i / 10;
-            let col = i % 10;
+        for i in 0..count {
+            let slot = match self.config.start_formation {
+                StartFormation::Waterfall => self.waterfall_slot(i),
+                StartFormation::Alley if i < inner_count => self.alley_slot(i, false),
+                StartFormation::Alley => self.alley_slot(i - inner_count, true),
+                StartFormation::LaneStagger => self.lane_stagger_slot(i),
+            };
+            self.place_runner(i, slot);
+        }
+    }
 
-            let start_distance = -(row as f32) * spread;
-            let lane = 0.8 + (col as f32) * 0.15 + rand::random::<f32>() * 0.05;
+    /// Add a human-controlled runner to a field that is already lined up,
+    /// giving it the next free start slot without moving anyone else
+    pub fn join_player(&mut self, player: &PlayerConfig) -> u32 {
+        let i = self.runners.len();
+        let id = self.add_player(player);
 
-            runner.reset(start_distance, lane);
-        }
+        let slot = match self.config.start_formation {
+            StartFormation::Waterfall => self.waterfall_slot(i),
+            StartFormation::Alley => {
+                // Only the outer alley starts ahead of the common line
+                let outer = self.runners[..i].iter().filter(|r| r.lane_assignment.stagger > 0.0).count();
+                if outer < Self::alley_outer_count(i + 1) {
+                    self.alley_slot(outer, true)
+                } else {
+                    self.alley_slot(i - outer, false)
+                }
+            }
+            StartFormation::LaneStagger => self.lane_stagger_slot(i),
+        };
+        self.place_runner(i, slot);
+        id
+    }
+
+    /// Put the runner at `i` on the line in its `(row, lane)` slot
+    fn place_runner(&mut self, i: usize, (row, lane): (usize, LaneAssignment)) {
+        let start_distance = lane.stagger - (row as f32) * self.config.formation_spread;
+        self.runners[i].reset(start_distance, lane, &mut self.rng);
+    }
+
+    /// Runners starting in the outer alley out of a field of `count`
+    fn alley_outer_count(count: usize) -> usize {
+        (count as f32 * Self::ALLEY_OUTER_SHARE).round() as usize
+    }
+
+    /// Waterfall: rows of 10 across lanes 1-2, no lane lock
+    fn waterfall_slot(&mut self, i: usize) -> (usize, LaneAssignment) {
+        let row = i / Self::ROW_WIDTH;
+        let col = i % Self::ROW_WIDTH;
+        let lane_position = 0.8 + (col as f32) * 0.15 + self.rng.gen::<f32>() * 0.05;
+
+        (row, LaneAssignment {
+            lane: (lane_position.round() as u32).max(1),
+            lane_position,
+            ..Default::default()
+        })
+    }
+
+    /// Alley: inner group across lanes 1-4, outer group across lanes 5-8
+    /// starting one bend's stagger ahead, both holding position until the
+    /// break. `index` counts from the front of the runner's own group.
+    fn alley_slot(&mut self, index: usize, outer: bool) -> (usize, LaneAssignment) {
+        let (first_lane, stagger) = if outer {
+            let lane = Self::ALLEY_OUTER_LANE;
+            (lane, track::stagger_distance(lane, 1))
+        } else {
+            (1, 0.0)
+        };
+
+        let row = index / Self::ROW_WIDTH;
+        let col = index % Self::ROW_WIDTH;
+        let lane_position = first_lane as f32 - 0.2 + (col as f32) * 0.35 + self.rng.gen::<f32>() * 0.05;
+
+        (row, LaneAssignment {
+            lane: (lane_position.round() as u32).max(first_lane),
+            lane_position,
+            stagger,
+            locked_until: Some(Self::LANE_BREAK_DISTANCE),
+        })
+    }
+
+    /// Lane stagger: one runner per lane (extra runners line up behind),
+    /// staggered for the bends run in lanes. Races up to 400m stay in lanes
+    /// throughout; longer races break after the first bend.
+    fn lane_stagger_slot(&self, i: usize) -> (usize, LaneAssignment) {
+        let distance = self.config.distance;
+        let lanes = track::NUM_LANES as usize;
+        let lane = (i % lanes) as u32 + 1;
+
+        let (bends, locked_until) = if distance <= 400.0 {
+            ((distance / 200.0).floor() as u32, Some(distance))
+        } else {
+            (1, Some(Self::LANE_BREAK_DISTANCE))
+        };
+
+        (i / lanes, LaneAssignment {
+            lane,
+            lane_position: lane as f32,
+            stagger: track::stagger_distance(lane, bends),
+            locked_until,
+        })
     }
 
     /// Start countdown
     pub fn start_countdown(&mut self) {
-        self.status = RaceStatus::Countdown;
+        self.set_status(RaceStatus::Countdown);
         self.countdown = 3.0;
     }
 
+    /// Move on to `status`, which must follow the current one
+    fn set_status(&mut self, status: RaceStatus) {
+        debug_assert!(
+            self.status.can_transition_to(status),
+            "race can't go from {:?} to {:?}",
+            self.status,
+            status
+        );
+        self.status = status;
+    }
+
     /// Update race state
     pub fn update(&mut self, delta: f32) {
         match self.status {
@@ -141,39 +708,93 @@ impl Race {
             RaceStatus::Countdown => {
                 self.countdown -= delta;
                 if self.countdown <= 0.0 {
-                    self.status = RaceStatus::Racing;
+                    self.set_status(RaceStatus::Racing);
                     self.countdown = 0.0;
+                    self.start_tracking();
                 }
             }
 
             RaceStatus::Racing => {
-                self.elapsed_time += delta * self.config.time_scale;
+                // The race clock runs `time_scale` times slower than real time, like the runners
+                self.elapsed_time += delta / self.config.time_scale;
+                let variability = self.config.variability();
+
+                let time = self.elapsed_time;
 
                 // Update all runners
-                for runner in &mut self.runners {
+                for (i, runner) in self.runners.iter_mut().enumerate() {
                     if !runner.flags.finished {
+                        let previous = runner.distance;
+                        let laps_before = Self::laps_to_go(self.config.distance, runner.progress());
                         Runner::update(
                             runner,
                             delta,
                             self.config.time_scale,
                             self.config.distance,
                         );
+                        if !runner.is_player() {
+                            Variability::update(&mut runner.form, runner.distance - previous, variability, &mut self.rng);
+                        }
+
+                        let logged = runner.split_log.len();
+                        Self::record_splits(runner, &self.timing_points, time);
+                        for index in logged..runner.split_log.len() {
+                            self.split_counts[index] += 1;
+                            let previous_time = index.checked_sub(1).map_or(0.0, |p| runner.split_log[p]);
+                            self.events.push(RaceEvent::SplitCrossed {
+                                time,
+                                runner_id: runner.id,
+                                distance: self.timing_points[index],
+                                split: runner.split_log[index] - previous_time,
+                                place: self.split_counts[index],
+                            });
+                        }
+
+                        // Crossing the line with laps left to run
+                        let laps_to_go = Self::laps_to_go(self.config.distance, runner.progress());
+                        if laps_to_go < laps_before && laps_to_go > 0 {
+                            self.events.push(RaceEvent::LapCompleted { time, runner_id: runner.id, laps_to_go });
+                            if laps_to_go == 1 && !self.bell_rung {
+                                self.bell_rung = true;
+                                self.events.push(RaceEvent::BellLap { time, runner_id: runner.id });
+                            }
+                        }
 
                         // Check for finish
-                        if runner.flags.finished && !self.finish_order.iter().any(|r| r.runner_id == runner.id) {
+                        if runner.flags.finished && self.finish_places[i] == 0 {
+                            let position = (self.finish_order.len() + 1) as u32;
+                            self.finish_places[i] = position;
                             self.finish_order.push(RaceResult {
                                 runner_id: runner.id,
                                 runner_name: runner.name.clone(),
-                                finish_time: self.elapsed_time,
-                                position: (self.finish_order.len() + 1) as u32,
+                                finish_time: time,
+                                position,
+                                splits: Self::recorded_splits(runner, &self.timing_points),
+                                strategy: runner.strategy,
+                                bib: runner.bib,
+                                player: runner.is_player(),
+                                flags: Vec::new(),
                             });
+                            self.events.push(RaceEvent::RunnerFinished { time, runner_id: runner.id, position });
                         }
                     }
                 }
 
+                // Keep runners from passing through each other, then find
+                // who is sitting in someone's slipstream for the next tick
+                self.collision_grid.rebuild(&self.runners);
+                Collision::resolve(&mut self.runners, &self.collision_grid, delta);
+                Drafting::detect(&mut self.runners, &self.collision_grid);
+                self.update_order();
+                self.update_placings();
+
                 // Check if all runners finished
                 if self.finish_order.len() == self.runners.len() {
-                    self.status = RaceStatus::Finished;
+                    self.set_status(RaceStatus::Finished);
+                    self.events.push(RaceEvent::RaceFinished {
+                        time,
+                        winner_id: self.finish_order.first().map(|r| r.runner_id),
+                    });
                 }
             }
 
@@ -191,22 +812,306 @@ impl Race {
         }
     }
 
+    /// Laps a runner still has to start: how many times they will cross
+    /// the finish line before finishing, counting the finish itself.
+    /// Runners lined up behind the start line count from the line, so
+    /// reaching it isn't a completed lap.
+    fn laps_to_go(race_distance: f32, progress: f32) -> u32 {
+        ((race_distance - progress.max(0.0)) / track::LAP_LENGTH).ceil().max(0.0) as u32
+    }
+
+    /// Set up running order and event tracking as the gun goes
+    fn start_tracking(&mut self) {
+        let count = self.runners.len();
+        self.order = (0..count).collect();
+        self.order
+            .sort_by(|&a, &b| self.runners[b].progress().total_cmp(&self.runners[a].progress()));
+        self.finish_places = vec![0; count];
+        self.split_counts = vec![0; self.timing_points.len()];
+        self.bell_rung = false;
+        self.front_times.clear();
+        self.update_placings();
+        self.reported_places = self.placings.iter().map(|p| p.place).collect();
+        self.events.push(RaceEvent::RaceStart { time: self.elapsed_time });
+    }
+
+    /// How far a runner must get ahead of the one in front before it counts
+    /// as a pass, so runners side by side don't swap places every step
+    const PASS_MARGIN: f32 = 0.1;
+
+    /// Whether runner `a` has got ahead of runner `b`, who was in front
+    fn has_passed(&self, a: usize, b: usize) -> bool {
+        match (self.finish_places[a], self.finish_places[b]) {
+            (0, 0) => self.runners[a].progress() > self.runners[b].progress() + Self::PASS_MARGIN,
+            (0, _) => false,
+            (_, 0) => true,
+            (place_a, place_b) => place_a < place_b,
+        }
+    }
+
+    /// Re-rank the field after a step, logging passes and lead changes.
+    /// Insertion sort on the last order: runners only move a place or two
+    /// per step, and each swap is one runner passing another.
+    fn update_order(&mut self) {
+        let Some(&leader) = self.order.first() else { return };
+        let time = self.elapsed_time;
+
+        for i in 1..self.order.len() {
+            let mut j = i;
+            while j > 0 && self.has_passed(self.order[j], self.order[j - 1]) {
+                self.order.swap(j, j - 1);
+                let (runner, passed) = (&self.runners[self.order[j - 1]], &self.runners[self.order[j]]);
+                self.events.push(RaceEvent::Pass {
+                    time,
+                    runner_id: runner.id,
+                    passed_id: passed.id,
+                    place: j as u32,
+                    to_go: self.config.distance - runner.progress(),
+                });
+                j -= 1;
+            }
+        }
+
+        if self.order[0] != leader {
+            let runner = &self.runners[self.order[0]];
+            self.events.push(RaceEvent::LeadChange {
+                time,
+                runner_id: runner.id,
+                previous_id: self.runners[leader].id,
+                to_go: self.config.distance - runner.progress(),
+            });
+        }
+    }
+
+    /// Work out every runner's place and gaps from the running order.
+    /// Time gaps are how long ago the front of the race was where the
+    /// runner is now; finishers are timed against the winner.
+    fn update_placings(&mut self) {
+        let Some(&leader) = self.order.first() else { return };
+        let time = self.elapsed_time;
+        let reached = |runner: &RunnerState| runner.progress().clamp(0.0, self.config.distance);
+
+        let front = reached(&self.runners[leader]);
+        while (self.front_times.len() as f32) <= front {
+            self.front_times.push(time);
+        }
+
+        let winner_time = self.finish_order.first().map(|r| r.finish_time);
+        self.placings.resize(self.runners.len(), Placing::default());
+        let mut ahead = Placing::default();
+
+        for (place, &i) in self.order.iter().enumerate() {
+            let runner = &self.runners[i];
+            let progress = reached(runner);
+            let gap_time = match (self.finish_places[i], winner_time) {
+                (0, _) | (_, None) => time - self.front_time(progress),
+                (position, Some(winner_time)) => self.finish_order[position as usize - 1].finish_time - winner_time,
+            };
+
+            let mut placing = Placing {
+                place: place as u32 + 1,
+                gap: front - progress,
+                gap_time: gap_time.max(0.0),
+                ..Placing::default()
+            };
+            if place > 0 {
+                placing.interval = (placing.gap - ahead.gap).max(0.0);
+                placing.interval_time = (placing.gap_time - ahead.gap_time).max(0.0);
+            } else {
+                placing.gap_time = 0.0;
+            }
+            self.placings[i] = placing;
+            ahead = placing;
+        }
+    }
+
+    /// Race time the front of the race reached `progress`
+    fn front_time(&self, progress: f32) -> f32 {
+        let meter = progress as usize;
+        match (self.front_times.get(meter), self.front_times.get(meter + 1)) {
+            (Some(&from), Some(&to)) => from + (to - from) * progress.fract(),
+            (Some(&from), None) => from,
+            _ => self.elapsed_time,
+        }
+    }
+
+    /// Live placing of a runner (`None` until the gun)
+    pub fn get_placing(&self, id: u32) -> Option<Placing> {
+        let index = self.runners.iter().position(|r| r.id == id)?;
+        self.placings.get(index).copied()
+    }
+
+    /// Take the place changes since the last call
+    pub fn take_place_changes(&mut self) -> Vec<PlaceChange> {
+        self.reported_places.resize(self.placings.len(), 0);
+        let mut changes = Vec::new();
+        for (i, placing) in self.placings.iter().enumerate() {
+            let from = self.reported_places[i];
+            if placing.place != from {
+                changes.push(PlaceChange { runner_id: self.runners[i].id, from, to: placing.place });
+                self.reported_places[i] = placing.place;
+            }
+        }
+        changes
+    }
+
+    /// Runners in running order, leader first (empty until the gun)
+    pub fn running_order(&self) -> impl Iterator<Item = &RunnerState> + '_ {
+        self.order.iter().map(|&i| &self.runners[i])
+    }
+
+    /// Take the events logged since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<RaceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Log the race time for every timing point the runner has now passed.
+    /// The last point is the finish, which only counts once the runner is
+    /// marked finished so its time matches `finish_time`.
+    fn record_splits(runner: &mut RunnerState, timing_points: &[f32], elapsed_time: f32) {
+        let progress = runner.progress();
+        let finish = timing_points.len().saturating_sub(1);
+
+        while let Some(&point) = timing_points.get(runner.split_log.len()) {
+            let passed = if runner.split_log.len() == finish {
+                runner.flags.finished
+            } else {
+                runner.flags.finished || progress >= point
+            };
+            if !passed {
+                break;
+            }
+            runner.split_log.push(elapsed_time);
+        }
+    }
+
+    /// A runner's logged split times paired with their timing points
+    fn recorded_splits(runner: &RunnerState, timing_points: &[f32]) -> Vec<RecordedSplit> {
+        let mut previous = 0.0;
+        runner
+            .split_log
+            .iter()
+            .zip(timing_points)
+            .map(|(&time, &distance)| {
+                let split = time - previous;
+                previous = time;
+                RecordedSplit { distance, time, split }
+            })
+            .collect()
+    }
+
+    /// Splits recorded so far for one runner
+    pub fn get_splits(&self, runner_id: u32) -> Option<RunnerSplits> {
+        self.runners.iter().find(|r| r.id == runner_id).map(|runner| RunnerSplits {
+            runner_id,
+            splits: Self::recorded_splits(runner, &self.timing_points),
+        })
+    }
+
+    /// Splits recorded so far for every runner
+    pub fn get_all_splits(&self) -> Vec<RunnerSplits> {
+        self.runners
+            .iter()
+            .map(|runner| RunnerSplits {
+                runner_id: runner.id,
+                splits: Self::recorded_splits(runner, &self.timing_points),
+            })
+            .collect()
+    }
+
+    /// Slowest pace (m/s) `run_to_finish` waits for
+    const MIN_FINISH_SPEED: f32 = 0.5;
+
+    /// Run the race to the finish as fast as possible, in fixed steps of `delta`.
+    /// Gives up and returns false if the race clock passes the time the field
+    /// would take at `MIN_FINISH_SPEED` (e.g. a player nobody is steering).
+    pub fn run_to_finish(&mut self, delta: f32) -> bool {
+        if self.status == RaceStatus::NotStarted {
+            self.start_countdown();
+        }
+
+        let time_limit = self.config.distance / Self::MIN_FINISH_SPEED;
+        while self.status != RaceStatus::Finished {
+            if self.elapsed_time > time_limit {
+                return false;
+            }
+            self.update(delta);
+        }
+        true
+    }
+
     /// Get compact snapshot for IPC transfer
     pub fn get_snapshot(&self) -> RaceSnapshot {
         RaceSnapshot {
             status: self.status,
             elapsed_time: self.elapsed_time,
             countdown: self.countdown,
-            runners: self.runners.iter().map(RunnerSnapshot::from).collect(),
+            runners: self
+                .runners
+                .iter()
+                .enumerate()
+                .map(|(i, r)| self.runner_snapshot(r, self.placings.get(i)))
+                .collect(),
             finisher_count: self.finish_order.len() as u32,
         }
     }
 
-    /// Get current leader
+    /// Snapshot a single runner with their placing, adding world placement if configured
+    fn runner_snapshot(&self, runner: &RunnerState, placing: Option<&Placing>) -> RunnerSnapshot {
+        let mut snapshot = RunnerSnapshot::from(runner);
+        if let Some(placing) = placing {
+            snapshot.place = placing.place;
+            snapshot.gap = placing.gap;
+            snapshot.gap_time = placing.gap_time;
+            snapshot.interval = placing.interval;
+            snapshot.interval_time = placing.interval_time;
+        }
+        if self.config.world_positions {
+            snapshot.world = Some(track::track_position(runner.distance, runner.lane_position));
+        }
+        snapshot
+    }
+
+    /// Get current leader (the furthest runner before the gun)
     pub fn get_leader(&self) -> Option<&RunnerState> {
-        self.runners.iter().max_by(|a, b| {
-            a.distance.partial_cmp(&b.distance).unwrap()
-        })
+        self.running_order()
+            .next()
+            .or_else(|| self.runners.iter().max_by(|a, b| a.progress().total_cmp(&b.progress())))
+    }
+
+    /// Get the human player's runner
+    pub fn get_player(&self) -> Option<&RunnerState> {
+        self.runners.iter().find(|r| r.is_player())
+    }
+
+    /// Apply the latest input to the first (local) player. Returns false if the race has no player.
+    pub fn set_player_input(&mut self, input: PlayerInput) -> bool {
+        match self.runners.iter_mut().find(|r| r.is_player()) {
+            Some(runner) => {
+                runner.control = RunnerControl::Player(input.clamped());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply the latest input to a specific player runner. Returns false if
+    /// the runner does not exist or is not player-controlled.
+    pub fn set_runner_input(&mut self, id: u32, input: PlayerInput) -> bool {
+        match self.runners.iter_mut().find(|r| r.id == id && r.is_player()) {
+            Some(runner) => {
+                runner.control = RunnerControl::Player(input.clamped());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Hand a player's runner over to the AI (e.g. on disconnect) so the race can still finish
+    pub fn release_player(&mut self, id: u32) {
+        if let Some(runner) = self.runners.iter_mut().find(|r| r.id == id) {
+            runner.control = RunnerControl::Ai;
+        }
     }
 
     /// Get runner by ID
@@ -224,3 +1129,274 @@ pub struct RaceSnapshot {
     pub runners: Vec<RunnerSnapshot>,
     pub finisher_count: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA: f32 = 1.0 / 60.0;
+
+    /// Finish time of a lone player holding `desired_speed` from the gun
+    fn player_finish_time(distance: f32, target_time: f32, desired_speed: f32) -> f32 {
+        let mut race = Race::new(RaceConfig {
+            distance,
+            runner_count: 0,
+            time_scale: 1.0,
+            seed: Some(7),
+            lobby: true,
+            ..RaceConfig::default()
+        });
+        race.generate_runners();
+        race.setup_starting_positions();
+        let id = race.join_player(&PlayerConfig { name: "Player".to_string(), target_time: Some(target_time) });
+        race.set_runner_input(id, PlayerInput { desired_speed, lane_change: 0.0 });
+
+        assert!(race.run_to_finish(DELTA));
+        race.finish_order[0].finish_time
+    }
+
+    /// A 400 m race with a full AI field, run to the finish
+    fn finished_race(seed: u64) -> Race {
+        run_race(RaceConfig {
+            distance: 400.0,
+            runner_count: 8,
+            time_scale: 1.0,
+            seed: Some(seed),
+            ..RaceConfig::default()
+        })
+    }
+
+    /// An AI race run to the finish
+    fn run_race(config: RaceConfig) -> Race {
+        let mut race = Race::new(config);
+        race.generate_runners();
+        race.setup_starting_positions();
+        race.start_countdown();
+
+        assert!(race.run_to_finish(DELTA));
+        race
+    }
+
+    /// Results as JSON, so whole races can be compared
+    fn results_json(race: &Race) -> String {
+        serde_json::to_string(&race.finish_order).unwrap()
+    }
+
+    #[test]
+    fn same_seed_gives_same_race() {
+        let (first, second) = (finished_race(42), finished_race(42));
+
+        assert_eq!(
+            serde_json::to_string(&first.get_entrants()).unwrap(),
+            serde_json::to_string(&second.get_entrants()).unwrap()
+        );
+        assert_eq!(results_json(&first), results_json(&second));
+    }
+
+    #[test]
+    fn different_seeds_give_different_races() {
+        assert_ne!(results_json(&finished_race(1)), results_json(&finished_race(2)));
+    }
+
+    #[test]
+    fn splits_recorded_at_every_lap() {
+        let race = run_race(RaceConfig {
+            distance: 1600.0,
+            runner_count: 4,
+            time_scale: 1.0,
+            seed: Some(11),
+            ..RaceConfig::default()
+        });
+
+        for result in &race.finish_order {
+            let distances: Vec<f32> = result.splits.iter().map(|s| s.distance).collect();
+            assert_eq!(distances, vec![400.0, 800.0, 1200.0, 1600.0]);
+            assert!(result.splits.windows(2).all(|pair| pair[1].time > pair[0].time));
+            assert_eq!(result.splits.last().unwrap().time, result.finish_time);
+
+            let total: f32 = result.splits.iter().map(|s| s.split).sum();
+            assert!((total - result.finish_time).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn timing_mats_are_sorted_and_end_at_the_finish() {
+        let config = RaceConfig {
+            distance: 1600.0,
+            timing_points: Some(TimingPoints::Mats(vec![1000.0, 300.0, 300.0, 2000.0, -5.0])),
+            ..RaceConfig::default()
+        };
+        assert_eq!(config.timing_distances(), vec![300.0, 1000.0, 1600.0]);
+    }
+
+    #[test]
+    fn mile_race_event_counts() {
+        let mut race = run_race(RaceConfig {
+            distance: 1600.0,
+            runner_count: 5,
+            time_scale: 1.0,
+            seed: Some(13),
+            ..RaceConfig::default()
+        });
+        let events = race.take_events();
+        let count = |f: fn(&RaceEvent) -> bool| events.iter().filter(|e| f(e)).count();
+
+        assert!(events.windows(2).all(|pair| pair[1].time() >= pair[0].time()));
+        assert!(matches!(events[0], RaceEvent::RaceStart { .. }));
+        assert_eq!(count(|e| matches!(e, RaceEvent::RaceStart { .. })), 1);
+        // Three laps completed each before the last one
+        assert_eq!(count(|e| matches!(e, RaceEvent::LapCompleted { .. })), 15);
+        assert_eq!(count(|e| matches!(e, RaceEvent::SplitCrossed { .. })), 20);
+        assert_eq!(count(|e| matches!(e, RaceEvent::RunnerFinished { .. })), 5);
+
+        // The bell goes once, for the first runner onto the last lap
+        let bells: Vec<u32> = events
+            .iter()
+            .filter_map(|e| match e {
+                RaceEvent::BellLap { runner_id, .. } => Some(*runner_id),
+                _ => None,
+            })
+            .collect();
+        let first_on_last_lap = events.iter().find_map(|e| match e {
+            RaceEvent::LapCompleted { runner_id, laps_to_go: 1, .. } => Some(*runner_id),
+            _ => None,
+        });
+        assert_eq!(bells.len(), 1);
+        assert_eq!(Some(bells[0]), first_on_last_lap);
+
+        let winner = race.finish_order[0].runner_id;
+        assert_eq!(
+            events.last(),
+            Some(&RaceEvent::RaceFinished { time: race.finish_order[4].finish_time, winner_id: Some(winner) })
+        );
+        for event in &events {
+            if let RaceEvent::LeadChange { runner_id, previous_id, .. } = event {
+                assert_ne!(runner_id, previous_id);
+            }
+        }
+    }
+
+    /// Live placings in running order, leader first
+    fn placings(race: &Race) -> Vec<Placing> {
+        race.running_order().map(|r| race.get_placing(r.id).unwrap()).collect()
+    }
+
+    #[test]
+    fn placings_mid_race() {
+        let mut race = Race::new(RaceConfig {
+            distance: 1600.0,
+            runner_count: 8,
+            time_scale: 1.0,
+            seed: Some(17),
+            ..RaceConfig::default()
+        });
+        race.generate_runners();
+        race.setup_starting_positions();
+        race.start_countdown();
+        // Countdown plus a minute of racing
+        for _ in 0..(63.0 / DELTA) as usize {
+            race.update(DELTA);
+        }
+
+        let placings = placings(&race);
+        let places: Vec<u32> = placings.iter().map(|p| p.place).collect();
+        assert_eq!(places, (1..=8).collect::<Vec<_>>());
+        assert_eq!((placings[0].gap, placings[0].gap_time, placings[0].interval), (0.0, 0.0, 0.0));
+
+        for pair in placings.windows(2) {
+            let (ahead, behind) = (pair[0], pair[1]);
+            assert!(behind.gap >= ahead.gap);
+            assert!((behind.interval - (behind.gap - ahead.gap)).abs() < 1e-3);
+            assert!(behind.interval_time >= 0.0);
+        }
+        let last = placings[7];
+        assert!(last.gap > 0.0 && last.gap_time > 0.0);
+    }
+
+    #[test]
+    fn placings_at_the_finish_use_finish_times() {
+        let race = run_race(RaceConfig {
+            distance: 400.0,
+            runner_count: 6,
+            time_scale: 1.0,
+            seed: Some(19),
+            ..RaceConfig::default()
+        });
+        let winner_time = race.finish_order[0].finish_time;
+
+        for result in &race.finish_order {
+            let placing = race.get_placing(result.runner_id).unwrap();
+            assert_eq!(placing.place, result.position);
+            assert!((placing.gap_time - (result.finish_time - winner_time)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn place_changes_are_reported_once() {
+        // Changes count from the order at the gun
+        let mut race = finished_race(23);
+        let changes = race.take_place_changes();
+
+        assert!(!changes.is_empty());
+        for change in &changes {
+            assert!(change.from > 0 && change.from != change.to);
+            assert_eq!(race.get_placing(change.runner_id).unwrap().place, change.to);
+        }
+        assert!(race.take_place_changes().is_empty());
+    }
+
+    /// Field named by the `InvalidConfig` error `config` is rejected with
+    fn invalid_field(config: RaceConfig) -> &'static str {
+        match config.validate() {
+            Err(GameError::InvalidConfig { field, .. }) => field,
+            other => panic!("expected InvalidConfig, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validation_names_the_bad_field() {
+        let base = RaceConfig::default;
+        assert_eq!(base().validate(), Ok(()));
+
+        let player = |name: &str, target_time| Some(PlayerConfig { name: name.to_string(), target_time });
+        let cases = [
+            (RaceConfig { distance: f32::NAN, ..base() }, "distance"),
+            (RaceConfig { distance: -400.0, ..base() }, "distance"),
+            (RaceConfig { runner_count: 0, ..base() }, "runner_count"),
+            (RaceConfig { runner_count: RaceConfig::MAX_RUNNERS + 1, ..base() }, "runner_count"),
+            (RaceConfig { time_scale: 0.0, ..base() }, "time_scale"),
+            (RaceConfig { formation_spread: -1.0, ..base() }, "formation_spread"),
+            (RaceConfig { split_interval: Some(SplitInterval::Custom(0.5)), ..base() }, "split_interval"),
+            (RaceConfig { timing_points: Some(TimingPoints::Mats(vec![f32::NAN])), ..base() }, "timing_points"),
+            (RaceConfig { pacing_mix: Some(PacingMix::from([(PacingStrategy::Even, -1.0)])), ..base() }, "pacing_mix"),
+            (RaceConfig { variability: Some(f32::INFINITY), ..base() }, "variability"),
+            (RaceConfig { player: player(" ", None), ..base() }, "player"),
+            (RaceConfig { player: player("Player", Some(0.0)), ..base() }, "player"),
+        ];
+        for (config, field) in cases {
+            assert_eq!(invalid_field(config), field);
+        }
+    }
+
+    #[test]
+    fn empty_field_needs_a_player_or_lobby() {
+        let empty = || RaceConfig { runner_count: 0, ..RaceConfig::default() };
+        let player = Some(PlayerConfig { name: "Player".to_string(), target_time: None });
+
+        assert_eq!(RaceConfig { player, ..empty() }.validate(), Ok(()));
+        assert_eq!(RaceConfig { lobby: true, ..empty() }.validate(), Ok(()));
+        assert_eq!(RaceConfig { entrants: vec![1], ..empty() }.validate(), Err(GameError::NoRoster));
+    }
+
+    #[test]
+    fn faster_player_input_finishes_sooner() {
+        let times: Vec<f32> = [6.0, 7.0, 8.0, 9.0, 10.0]
+            .into_iter()
+            .map(|speed| player_finish_time(200.0, 25.0, speed))
+            .collect();
+
+        for pair in times.windows(2) {
+            assert!(pair[1] < pair[0], "finish times {:?} don't fall with speed", times);
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Replay - Race recording and playback
+//!
+//! Records `RaceSnapshot`s at a fixed interval while a race runs and plays
+//! them back through the same tick/snapshot interface as a live race. The
+//! frontend already interpolates between snapshots using `TickResult::alpha`,
+//! so frames don't need to be stored at the full tick rate.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::game_server::race::{Race, RaceConfig, RaceResult, RaceSnapshot, RaceStatus};
+
+/// Replay file format version
+pub const REPLAY_VERSION: u32 = 1;
+
+/// One recorded snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Seconds since the race was started (wall-clock at 1x playback)
+    pub time: f32,
+    pub snapshot: RaceSnapshot,
+}
+
+/// A recorded race
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub config: RaceConfig,
+    pub seed: u64,
+    /// Seconds between recorded frames
+    pub frame_interval: f32,
+    pub frames: Vec<ReplayFrame>,
+    /// Final results (empty if recording stopped before the finish)
+    pub results: Vec<RaceResult>,
+}
+
+impl Replay {
+    /// Length of the replay (seconds)
+    pub fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |f| f.time)
+    }
+
+    /// Write the replay to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+
+    /// Read a replay from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let replay: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+        if replay.version != REPLAY_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported replay version {}", replay.version),
+            ));
+        }
+        if replay.frames.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Replay has no frames"));
+        }
+
+        Ok(replay)
+    }
+}
+
+/// Records a running race into a `Replay`
+pub struct ReplayRecorder {
+    replay: Replay,
+    /// Time since recording started
+    clock: f32,
+    /// Time the next frame is due
+    next_frame: f32,
+}
+
+impl ReplayRecorder {
+    /// Default recording rate (10 frames per second)
+    pub const DEFAULT_FRAME_INTERVAL: f32 = 0.1;
+
+    /// Start recording a race, capturing its current state as the first frame
+    pub fn new(race: &Race, frame_interval: f32) -> Self {
+        let mut recorder = Self {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                config: race.config.clone(),
+                seed: race.seed,
+                frame_interval,
+                frames: Vec::new(),
+                results: Vec::new(),
+            },
+            clock: 0.0,
+            next_frame: 0.0,
+        };
+        recorder.push_frame(race);
+        recorder
+    }
+
+    /// Record after the race advanced by `delta` seconds. Frames are kept
+    /// every `frame_interval`, plus the final frame at the finish.
+    pub fn record(&mut self, delta: f32, race: &Race) {
+        self.clock += delta;
+
+        let finished = race.status == RaceStatus::Finished;
+        // Half a step of slack so float drift doesn't skip a frame
+        if finished || self.clock + delta * 0.5 >= self.next_frame {
+            self.push_frame(race);
+        }
+        if finished {
+            self.replay.results = race.finish_order.clone();
+        }
+    }
+
+    fn push_frame(&mut self, race: &Race) {
+        self.replay.frames.push(ReplayFrame {
+            time: self.clock,
+            snapshot: race.get_snapshot(),
+        });
+        self.next_frame += self.replay.frame_interval;
+    }
+
+    /// The replay recorded so far
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Stop recording and return the replay
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// Playback position and status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayInfo {
+    pub seed: u64,
+    pub distance: f32,
+    pub duration: f32,
+    pub time: f32,
+    pub speed: f32,
+    pub frame_count: u32,
+}
+
+/// Plays a `Replay` back frame by frame
+pub struct ReplayPlayer {
+    replay: Replay,
+    /// Playback position (seconds)
+    time: f32,
+    /// Playback rate (negative plays backwards)
+    speed: f32,
+}
+
+impl ReplayPlayer {
+    /// Fastest playback rate in either direction
+    pub const MAX_SPEED: f32 = 16.0;
+
+    /// Create a player positioned at the start of the replay
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            time: 0.0,
+            speed: 1.0,
+        }
+    }
+
+    /// Advance playback by `frame_time` wall-clock seconds. Returns how many
+    /// recorded frames were passed.
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        let before = self.frame_index();
+        self.time = (self.time + frame_time * self.speed).clamp(0.0, self.replay.duration());
+        self.frame_index().abs_diff(before) as u32
+    }
+
+    /// Jump to a playback position (seconds). A non-finite time is ignored.
+    pub fn seek(&mut self, time: f32) {
+        if time.is_finite() {
+            self.time = time.clamp(0.0, self.replay.duration());
+        }
+    }
+
+    /// Set the playback rate. A non-finite rate is ignored.
+    pub fn set_speed(&mut self, speed: f32) {
+        if speed.is_finite() {
+            self.speed = speed.clamp(-Self::MAX_SPEED, Self::MAX_SPEED);
+        }
+    }
+
+    /// Whether playback has reached the end (or the start, when rewinding)
+    pub fn is_finished(&self) -> bool {
+        if self.speed < 0.0 {
+            self.time <= 0.0
+        } else {
+            self.time >= self.replay.duration()
+        }
+    }
+
+    /// Index of the last frame at or before the playback position
+    fn frame_index(&self) -> usize {
+        self.replay
+            .frames
+            .partition_point(|f| f.time <= self.time)
+            .saturating_sub(1)
+    }
+
+    /// Snapshot at the playback position
+    pub fn snapshot(&self) -> RaceSnapshot {
+        self.replay.frames[self.frame_index()].snapshot.clone()
+    }
+
+    /// Fraction of the way to the next frame (0-1), for render interpolation
+    pub fn alpha(&self) -> f32 {
+        let index = self.frame_index();
+        match (self.replay.frames.get(index), self.replay.frames.get(index + 1)) {
+            (Some(current), Some(next)) if next.time > current.time => {
+                ((self.time - current.time) / (next.time - current.time)).clamp(0.0, 1.0)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// The replay being played
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Current playback position and status
+    pub fn info(&self) -> ReplayInfo {
+        ReplayInfo {
+            seed: self.replay.seed,
+            distance: self.replay.config.distance,
+            duration: self.replay.duration(),
+            time: self.time,
+            speed: self.speed,
+            frame_count: self.replay.frames.len() as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA: f32 = 1.0 / 60.0;
+
+    /// Record a short race from the countdown to the finish
+    fn recorded_race() -> Replay {
+        let mut race = Race::new(RaceConfig {
+            distance: 200.0,
+            runner_count: 4,
+            time_scale: 1.0,
+            seed: Some(9),
+            ..RaceConfig::default()
+        });
+        race.generate_runners();
+        race.setup_starting_positions();
+        race.start_countdown();
+
+        let mut recorder = ReplayRecorder::new(&race, ReplayRecorder::DEFAULT_FRAME_INTERVAL);
+        while race.status != RaceStatus::Finished {
+            race.update(DELTA);
+            recorder.record(DELTA, &race);
+        }
+        recorder.finish()
+    }
+
+    #[test]
+    fn recording_ends_with_the_finish() {
+        let replay = recorded_race();
+        let last = replay.frames.last().unwrap();
+
+        assert_eq!(replay.frames[0].time, 0.0);
+        assert_eq!(last.snapshot.status, RaceStatus::Finished);
+        assert_eq!(replay.results.len(), 4);
+        assert!(replay.frames.windows(2).all(|pair| pair[1].time > pair[0].time));
+    }
+
+    #[test]
+    fn seek_clamps_to_the_replay() {
+        let mut player = ReplayPlayer::new(recorded_race());
+        let duration = player.replay().duration();
+
+        player.seek(duration + 10.0);
+        assert_eq!(player.info().time, duration);
+        assert!(player.is_finished());
+
+        player.seek(-5.0);
+        assert_eq!(player.info().time, 0.0);
+
+        player.seek(f32::NAN);
+        assert_eq!(player.info().time, 0.0);
+    }
+
+    #[test]
+    fn seek_shows_the_frame_at_or_before() {
+        let replay = recorded_race();
+        let frame = &replay.frames[20];
+        let (time, elapsed) = (frame.time, frame.snapshot.elapsed_time);
+        let mut player = ReplayPlayer::new(replay);
+
+        player.seek(time + 0.05);
+        assert_eq!(player.snapshot().elapsed_time, elapsed);
+        assert!((0.0..1.0).contains(&player.alpha()));
+    }
+
+    #[test]
+    fn speed_is_clamped() {
+        let mut player = ReplayPlayer::new(recorded_race());
+
+        player.set_speed(100.0);
+        assert_eq!(player.info().speed, ReplayPlayer::MAX_SPEED);
+        player.set_speed(-100.0);
+        assert_eq!(player.info().speed, -ReplayPlayer::MAX_SPEED);
+        player.set_speed(f32::INFINITY);
+        assert_eq!(player.info().speed, -ReplayPlayer::MAX_SPEED);
+    }
+
+    #[test]
+    fn reverse_play_finishes_at_the_start() {
+        let replay = recorded_race();
+        let first_status = replay.frames[0].snapshot.status;
+        let mut player = ReplayPlayer::new(replay);
+
+        player.seek(player.replay().duration());
+        player.set_speed(-4.0);
+        assert!(!player.is_finished());
+
+        let mut frames_passed = 0;
+        while !player.is_finished() {
+            frames_passed += player.advance(DELTA);
+        }
+        assert_eq!(player.info().time, 0.0);
+        assert_eq!(player.snapshot().status, first_status);
+        assert_eq!(frames_passed as usize, player.replay().frames.len() - 1);
+    }
+}
@@ -0,0 +1,413 @@
+//! Results - Local database of completed races
+//!
+//! Every finished race is stored in an SQLite file so results survive
+//! `reset_race` and app restarts. Personal bests, season bests and records
+//! are derived from the stored results, and each new result is flagged
+//! against them as it is recorded.
+//!
+//! Results are attributed across races by roster bib, or by name for the
+//! human player. Anonymous AI runners are stored but have no PBs.
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{params, Connection, Row, Transaction};
+use serde::{Deserialize, Serialize};
+use crate::game_server::race::{Race, RaceResult};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS races (
+        id INTEGER PRIMARY KEY,
+        recorded_at INTEGER NOT NULL,
+        distance REAL NOT NULL,
+        seed INTEGER NOT NULL,
+        runner_count INTEGER NOT NULL,
+        meet TEXT,
+        time_scale REAL NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS results (
+        race_id INTEGER NOT NULL REFERENCES races(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        runner_name TEXT NOT NULL,
+        bib INTEGER,
+        athlete TEXT,
+        finish_time REAL NOT NULL,
+        pb INTEGER NOT NULL DEFAULT 0,
+        sb INTEGER NOT NULL DEFAULT 0,
+        cr INTEGER NOT NULL DEFAULT 0,
+        mr INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (race_id, position)
+    );
+    CREATE INDEX IF NOT EXISTS results_athlete ON results (athlete);
+    CREATE INDEX IF NOT EXISTS races_distance ON races (distance, meet);
+";
+
+/// Columns selected for a `Mark`, best time per distance
+const MARK_COLUMNS: &str = "
+    SELECT r.runner_name, r.bib, ra.distance, MIN(r.finish_time), ra.id, ra.recorded_at, ra.meet
+    FROM results r JOIN races ra ON ra.id = r.race_id
+";
+
+/// Notable result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResultFlag {
+    /// Athlete's fastest ever time at the distance
+    PersonalBest,
+    /// Athlete's fastest time at the distance this year (not flagged with a PB)
+    SeasonBest,
+    /// Fastest time at the distance in any race
+    CourseRecord,
+    /// Fastest time at the distance at this meet
+    MeetRecord,
+}
+
+/// Who a result belongs to across races
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AthleteId {
+    /// Roster athlete
+    Bib(u32),
+    /// Human player, by name
+    Player(String),
+}
+
+impl AthleteId {
+    /// Identity of a result's runner, `None` for anonymous AI runners
+    pub fn of(result: &RaceResult) -> Option<Self> {
+        match result.bib {
+            Some(bib) => Some(Self::Bib(bib)),
+            None if result.player => Some(Self::Player(result.runner_name.clone())),
+            None => None,
+        }
+    }
+
+    fn key(&self) -> String {
+        match self {
+            Self::Bib(bib) => format!("bib:{}", bib),
+            Self::Player(name) => format!("player:{}", name),
+        }
+    }
+}
+
+/// Best time at a distance and where it was set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mark {
+    pub runner_name: String,
+    pub bib: Option<u32>,
+    /// Meters
+    pub distance: f32,
+    /// Seconds
+    pub time: f32,
+    pub race_id: i64,
+    /// Unix seconds
+    pub recorded_at: i64,
+    pub meet: Option<String>,
+}
+
+/// A stored race
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceSummary {
+    pub race_id: i64,
+    /// Unix seconds
+    pub recorded_at: i64,
+    pub distance: f32,
+    pub seed: u64,
+    pub runner_count: u32,
+    pub meet: Option<String>,
+    pub winner: Option<String>,
+    pub winning_time: Option<f32>,
+}
+
+/// One of an athlete's stored results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub race_id: i64,
+    /// Unix seconds
+    pub recorded_at: i64,
+    pub distance: f32,
+    pub meet: Option<String>,
+    pub position: u32,
+    pub finish_time: f32,
+    pub flags: Vec<ResultFlag>,
+}
+
+/// SQLite store of completed races
+pub struct ResultsDb {
+    /// Behind a lock so the server stays `Sync` for `SharedGameServer`
+    conn: Mutex<Connection>,
+}
+
+impl ResultsDb {
+    /// Open (creating if needed) the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Database that only lives as long as this value
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> rusqlite::Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // A panic mid-transaction rolls it back, so the connection is still usable
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Store a finished race and flag its results (in `race.finish_order`)
+    /// as PBs, season bests and records. Returns the new race ID.
+    pub fn record_race(&self, race: &mut Race, meet: Option<&str>) -> rusqlite::Result<i64> {
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let distance = f64::from(race.config.distance);
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO races (recorded_at, distance, seed, runner_count, meet, time_scale)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                recorded_at,
+                distance,
+                race.seed as i64,
+                race.runners.len() as u32,
+                meet,
+                f64::from(race.config.time_scale),
+            ],
+        )?;
+        let race_id = tx.last_insert_rowid();
+
+        // In finishing order, so each result is only compared with those ahead of it
+        for result in &mut race.finish_order {
+            let time = f64::from(result.finish_time);
+            let athlete = AthleteId::of(result).map(|a| a.key());
+            let beats = |best: Option<f64>| best.map_or(true, |best| time < best);
+            let mut flags = Vec::new();
+
+            if let Some(key) = &athlete {
+                let pb = Self::best(&tx, "r.athlete = ?2", params![distance, key])?;
+                let sb = Self::best(
+                    &tx,
+                    "r.athlete = ?2 AND strftime('%Y', ra.recorded_at, 'unixepoch') = strftime('%Y', ?3, 'unixepoch')",
+                    params![distance, key, recorded_at],
+                )?;
+                if beats(pb) {
+                    flags.push(ResultFlag::PersonalBest);
+                } else if beats(sb) {
+                    flags.push(ResultFlag::SeasonBest);
+                }
+            }
+            if beats(Self::best(&tx, "1", params![distance])?) {
+                flags.push(ResultFlag::CourseRecord);
+            }
+            if let Some(meet) = meet {
+                if beats(Self::best(&tx, "ra.meet = ?2", params![distance, meet])?) {
+                    flags.push(ResultFlag::MeetRecord);
+                }
+            }
+
+            tx.execute(
+                "INSERT INTO results (race_id, position, runner_name, bib, athlete, finish_time, pb, sb, cr, mr)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    race_id,
+                    result.position,
+                    result.runner_name,
+                    result.bib,
+                    athlete,
+                    time,
+                    flags.contains(&ResultFlag::PersonalBest),
+                    flags.contains(&ResultFlag::SeasonBest),
+                    flags.contains(&ResultFlag::CourseRecord),
+                    flags.contains(&ResultFlag::MeetRecord),
+                ],
+            )?;
+            result.flags = flags;
+        }
+
+        tx.commit()?;
+        Ok(race_id)
+    }
+
+    /// Fastest stored time at distance `?1` among results matching `filter`
+    fn best(tx: &Transaction, filter: &str, params: impl rusqlite::Params) -> rusqlite::Result<Option<f64>> {
+        tx.query_row(
+            &format!(
+                "SELECT MIN(r.finish_time) FROM results r JOIN races ra ON ra.id = r.race_id
+                 WHERE ra.distance = ?1 AND {}",
+                filter
+            ),
+            params,
+            |row| row.get(0),
+        )
+    }
+
+    /// An athlete's best time at every distance they have raced
+    pub fn personal_bests(&self, athlete: &AthleteId) -> rusqlite::Result<Vec<Mark>> {
+        self.marks("WHERE r.athlete = ?1", params![athlete.key()])
+    }
+
+    /// An athlete's best time at every distance in `season` (the current year if `None`)
+    pub fn season_bests(&self, athlete: &AthleteId, season: Option<i32>) -> rusqlite::Result<Vec<Mark>> {
+        self.marks(
+            "WHERE r.athlete = ?1
+             AND strftime('%Y', ra.recorded_at, 'unixepoch') = COALESCE(?2, strftime('%Y', 'now'))",
+            params![athlete.key(), season.map(|year| format!("{:04}", year))],
+        )
+    }
+
+    /// Fastest time at every distance, at one meet or (with `None`) in any race
+    pub fn records(&self, meet: Option<&str>) -> rusqlite::Result<Vec<Mark>> {
+        self.marks("WHERE ?1 IS NULL OR ra.meet = ?1", params![meet])
+    }
+
+    fn marks(&self, filter: &str, params: impl rusqlite::Params) -> rusqlite::Result<Vec<Mark>> {
+        let sql = format!("{} {} GROUP BY ra.distance ORDER BY ra.distance", MARK_COLUMNS, filter);
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&sql)?;
+        let marks = stmt.query_map(params, |row| {
+            Ok(Mark {
+                runner_name: row.get(0)?,
+                bib: row.get(1)?,
+                distance: row.get::<_, f64>(2)? as f32,
+                time: row.get::<_, f64>(3)? as f32,
+                race_id: row.get(4)?,
+                recorded_at: row.get(5)?,
+                meet: row.get(6)?,
+            })
+        })?;
+        marks.collect()
+    }
+
+    /// An athlete's results, newest first
+    pub fn history(&self, athlete: &AthleteId, limit: u32) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT ra.id, ra.recorded_at, ra.distance, ra.meet, r.position, r.finish_time, r.pb, r.sb, r.cr, r.mr
+             FROM results r JOIN races ra ON ra.id = r.race_id
+             WHERE r.athlete = ?1 ORDER BY ra.recorded_at DESC, ra.id DESC LIMIT ?2",
+        )?;
+        let entries = stmt.query_map(params![athlete.key(), limit], |row| {
+            Ok(HistoryEntry {
+                race_id: row.get(0)?,
+                recorded_at: row.get(1)?,
+                distance: row.get::<_, f64>(2)? as f32,
+                meet: row.get(3)?,
+                position: row.get(4)?,
+                finish_time: row.get::<_, f64>(5)? as f32,
+                flags: Self::flags(row, 6)?,
+            })
+        })?;
+        entries.collect()
+    }
+
+    /// Stored races, newest first
+    pub fn recent_races(&self, limit: u32) -> rusqlite::Result<Vec<RaceSummary>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT ra.id, ra.recorded_at, ra.distance, ra.seed, ra.runner_count, ra.meet, r.runner_name, r.finish_time
+             FROM races ra LEFT JOIN results r ON r.race_id = ra.id AND r.position = 1
+             ORDER BY ra.recorded_at DESC, ra.id DESC LIMIT ?1",
+        )?;
+        let races = stmt.query_map(params![limit], |row| {
+            Ok(RaceSummary {
+                race_id: row.get(0)?,
+                recorded_at: row.get(1)?,
+                distance: row.get::<_, f64>(2)? as f32,
+                seed: row.get::<_, i64>(3)? as u64,
+                runner_count: row.get(4)?,
+                meet: row.get(5)?,
+                winner: row.get(6)?,
+                winning_time: row.get::<_, Option<f64>>(7)?.map(|t| t as f32),
+            })
+        })?;
+        races.collect()
+    }
+
+    /// Flags stored in the four flag columns starting at `first`
+    fn flags(row: &Row, first: usize) -> rusqlite::Result<Vec<ResultFlag>> {
+        let all = [
+            ResultFlag::PersonalBest,
+            ResultFlag::SeasonBest,
+            ResultFlag::CourseRecord,
+            ResultFlag::MeetRecord,
+        ];
+        let mut flags = Vec::new();
+        for (i, flag) in all.into_iter().enumerate() {
+            if row.get::<_, bool>(first + i)? {
+                flags.push(flag);
+            }
+        }
+        Ok(flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_server::race::RaceConfig;
+    use ResultFlag::*;
+
+    /// A finished 1500 m race with these (bib, time) results in order
+    fn race(finishers: &[(Option<u32>, f32)]) -> Race {
+        let mut race = Race::new(RaceConfig { distance: 1500.0, seed: Some(1), ..RaceConfig::default() });
+        race.finish_order = finishers
+            .iter()
+            .zip(1..)
+            .map(|(&(bib, finish_time), position)| RaceResult {
+                runner_id: position,
+                runner_name: bib.map_or_else(|| format!("Runner {}", position), |bib| format!("Bib {}", bib)),
+                finish_time,
+                position,
+                splits: Vec::new(),
+                strategy: Default::default(),
+                bib,
+                player: false,
+                flags: Vec::new(),
+            })
+            .collect();
+        race
+    }
+
+    /// Record a race and return each result's flags
+    fn record(db: &ResultsDb, finishers: &[(Option<u32>, f32)], meet: Option<&str>) -> Vec<Vec<ResultFlag>> {
+        let mut race = race(finishers);
+        db.record_race(&mut race, meet).unwrap();
+        race.finish_order.into_iter().map(|r| r.flags).collect()
+    }
+
+    #[test]
+    fn flags_pbs_and_records_as_results_arrive() {
+        let db = ResultsDb::open_in_memory().unwrap();
+
+        let first = record(&db, &[(Some(1), 240.0), (Some(2), 245.0), (None, 250.0)], Some("Oslo"));
+        assert_eq!(first, vec![vec![PersonalBest, CourseRecord, MeetRecord], vec![PersonalBest], vec![]]);
+
+        let second = record(&db, &[(Some(2), 238.0), (Some(1), 241.0)], None);
+        assert_eq!(second, vec![vec![PersonalBest, CourseRecord], vec![]]);
+
+        let third = record(&db, &[(Some(1), 239.0)], Some("Oslo"));
+        assert_eq!(third, vec![vec![PersonalBest, MeetRecord]]);
+
+        let best = |marks: Vec<Mark>| marks.iter().map(|m| m.time).collect::<Vec<_>>();
+        assert_eq!(best(db.personal_bests(&AthleteId::Bib(1)).unwrap()), vec![239.0]);
+        assert_eq!(best(db.records(None).unwrap()), vec![238.0]);
+        assert_eq!(best(db.records(Some("Oslo")).unwrap()), vec![239.0]);
+    }
+
+    #[test]
+    fn season_best_only_counts_this_year() {
+        let db = ResultsDb::open_in_memory().unwrap();
+        record(&db, &[(Some(3), 250.0)], None);
+        // Move that race back to 1970
+        db.conn().execute("UPDATE races SET recorded_at = 0", []).unwrap();
+
+        assert_eq!(record(&db, &[(Some(3), 255.0)], None), vec![vec![SeasonBest]]);
+        assert_eq!(record(&db, &[(Some(3), 256.0)], None), vec![Vec::<ResultFlag>::new()]);
+    }
+}
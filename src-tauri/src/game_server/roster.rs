@@ -0,0 +1,122 @@
+//! Roster - Persistent athletes loaded from data files
+//!
+//! A roster is a JSON file of named athletes with bibs, kit, character
+//! model, personal bests and pacing tendencies. Races can draw their field
+//! from a roster so the same rivals turn up race after race.
+
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::game_server::pacing::PacingMix;
+use crate::game_server::race::Race;
+
+/// Kit colours (CSS colour strings, e.g. "#c8102e")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kit {
+    pub primary: String,
+    #[serde(default)]
+    pub secondary: Option<String>,
+}
+
+/// Best time over a distance
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PersonalBest {
+    /// Meters
+    pub distance: f32,
+    /// Seconds
+    pub time: f32,
+}
+
+impl PersonalBest {
+    /// Whether the distance and time are both positive numbers
+    pub fn is_valid(&self) -> bool {
+        self.distance.is_finite() && self.distance > 0.0 && self.time.is_finite() && self.time > 0.0
+    }
+}
+
+/// A named athlete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Athlete {
+    pub name: String,
+    /// Unique within a roster
+    pub bib: u32,
+    /// Country code (e.g. "KEN")
+    #[serde(default)]
+    pub nationality: Option<String>,
+    #[serde(default)]
+    pub kit: Option<Kit>,
+    /// Character model key (see CharacterPreview.js)
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub pbs: Vec<PersonalBest>,
+    /// How this athlete tends to race (the race's mix if not set)
+    #[serde(default)]
+    pub pacing: Option<PacingMix>,
+}
+
+impl Athlete {
+    /// Day-to-day spread around the predicted time (fraction)
+    const DAY_FORM: f32 = 0.015;
+
+    /// Expected time over `distance`, scaled from the PB at the closest
+    /// distance. `None` if the athlete has no PBs.
+    pub fn predicted_time(&self, distance: f32) -> Option<f32> {
+        let closest = self
+            .pbs
+            .iter()
+            .filter(|pb| pb.is_valid())
+            .min_by(|a, b| (a.distance / distance).ln().abs().total_cmp(&(b.distance / distance).ln().abs()))?;
+
+        Some(closest.time * (distance / closest.distance).powf(Race::RIEGEL_EXPONENT))
+    }
+
+    /// Finish time for today's race: the predicted time with a little day form
+    pub fn race_time<R: Rng + ?Sized>(&self, distance: f32, rng: &mut R) -> Option<f32> {
+        let form = 1.0 + rng.gen_range(-Self::DAY_FORM..Self::DAY_FORM);
+        self.predicted_time(distance).map(|time| time * form)
+    }
+}
+
+/// A collection of athletes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Roster {
+    pub athletes: Vec<Athlete>,
+}
+
+impl Roster {
+    /// Read a roster from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let roster: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        roster
+            .validate()
+            .map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason))?;
+        Ok(roster)
+    }
+
+    /// Check bibs are unique and every PB is usable
+    pub fn validate(&self) -> Result<(), String> {
+        let mut bibs: Vec<u32> = self.athletes.iter().map(|a| a.bib).collect();
+        bibs.sort_unstable();
+        if let Some(pair) = bibs.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(format!("Duplicate bib {} in roster", pair[0]));
+        }
+
+        for athlete in &self.athletes {
+            if let Some(pb) = athlete.pbs.iter().find(|pb| !pb.is_valid()) {
+                return Err(format!(
+                    "Bib {} has an invalid PB ({} s over {} m): time and distance must be positive",
+                    athlete.bib, pb.time, pb.distance
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up an athlete by bib
+    pub fn get(&self, bib: u32) -> Option<&Athlete> {
+        self.athletes.iter().find(|a| a.bib == bib)
+    }
+}
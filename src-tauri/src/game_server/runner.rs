@@ -3,49 +3,198 @@
 //! Each runner has position, speed, and race data (split times).
 //! The simulation updates all runners each tick.
 
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use crate::game_server::energy::EnergySystem;
+use crate::game_server::pacing::PacingStrategy;
+use crate::game_server::track::TrackPosition;
+use crate::game_server::variability::Form;
 
-/// Split times for a 5K race (5 x 1km splits)
+/// Distance between split points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SplitInterval {
+    /// Every 400m lap
+    Lap,
+    /// Every kilometer
+    Kilometer,
+    /// Every mile
+    Mile,
+    /// Custom interval in meters
+    Custom(f32),
+}
+
+impl SplitInterval {
+    /// Interval length in meters
+    pub fn meters(&self) -> f32 {
+        match self {
+            Self::Lap => 400.0,
+            Self::Kilometer => 1000.0,
+            Self::Mile => 1609.344,
+            Self::Custom(meters) => *meters,
+        }
+    }
+
+    /// Default interval for a race distance (per lap up to the mile, per km beyond)
+    pub fn for_distance(distance: f32) -> Self {
+        if distance <= 1600.0 {
+            Self::Lap
+        } else {
+            Self::Kilometer
+        }
+    }
+}
+
+/// Target split times for a race, one per split point
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SplitTimes {
-    pub splits: [f32; 5],
+    /// Distance of each split point from the start (last = race distance)
+    pub distances: Vec<f32>,
+    /// Cumulative target time at each split point
+    pub splits: Vec<f32>,
     pub final_time: f32,
 }
 
 impl SplitTimes {
-    /// Generate split times for a given finish time with slight variation
-    pub fn from_finish_time(finish_time: f32) -> Self {
-        let km_time = finish_time / 5.0;
-        let variation = || 0.98 + rand::random::<f32>() * 0.04;
+    /// Generate split times for a given finish time, shaped by the pacing
+    /// strategy with slight variation per segment, normalised so the splits
+    /// add up to the finish time
+    pub fn from_finish_time<R: Rng + ?Sized>(
+        finish_time: f32,
+        race_distance: f32,
+        interval: SplitInterval,
+        strategy: PacingStrategy,
+        rng: &mut R,
+    ) -> Self {
+        let distances = Self::split_distances(race_distance, interval.meters());
+
+        let mut previous = 0.0;
+        let segment_times: Vec<f32> = distances
+            .iter()
+            .map(|&distance| {
+                let even_time = finish_time * (distance - previous) / race_distance;
+                let midpoint = (previous + distance) / 2.0 / race_distance;
+                previous = distance;
+                even_time * strategy.segment_weight(midpoint) * (0.98 + rng.gen::<f32>() * 0.04)
+            })
+            .collect();
+
+        let scale = finish_time / segment_times.iter().sum::<f32>();
+        let mut elapsed = 0.0;
+        let mut splits: Vec<f32> = segment_times
+            .iter()
+            .map(|segment_time| {
+                elapsed += segment_time * scale;
+                elapsed
+            })
+            .collect();
+
+        // Pin the last split exactly to the finish time
+        if let Some(last) = splits.last_mut() {
+            *last = finish_time;
+        }
 
         Self {
-            splits: [
-                km_time * variation(),
-                km_time * 2.0 * variation(),
-                km_time * 3.0 * variation(),
-                km_time * 4.0 * variation(),
-                finish_time,
-            ],
+            distances,
+            splits,
             final_time: finish_time,
         }
     }
 
+    /// Split point distances for a race: every `interval` meters, plus the finish
+    pub(crate) fn split_distances(race_distance: f32, interval: f32) -> Vec<f32> {
+        let count = (race_distance / interval).ceil().max(1.0) as usize;
+
+        (1..=count)
+            .map(|i| (i as f32 * interval).min(race_distance))
+            .collect()
+    }
+
     /// Get target speed at a given distance
     pub fn get_target_speed(&self, distance: f32, time_scale: f32) -> f32 {
-        let segment = (distance / 1000.0).floor().min(4.0) as usize;
-        let time_at_start = if segment == 0 { 0.0 } else { self.splits[segment - 1] };
-        let time_at_end = self.splits[segment];
-        let segment_time = time_at_end - time_at_start;
+        let last = self.splits.len() - 1;
+        let segment = self.distances.partition_point(|&d| d <= distance).min(last);
+
+        let (distance_at_start, time_at_start) = if segment == 0 {
+            (0.0, 0.0)
+        } else {
+            (self.distances[segment - 1], self.splits[segment - 1])
+        };
+        let segment_length = self.distances[segment] - distance_at_start;
+        let segment_time = self.splits[segment] - time_at_start;
+
+        (segment_length / segment_time) / time_scale
+    }
+
+    /// Race distance these splits cover
+    pub fn race_distance(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+}
 
-        (1000.0 / segment_time) / time_scale
+/// Start lane assignment for a runner
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LaneAssignment {
+    /// Assigned lane (1 = inside)
+    pub lane: u32,
+    /// Lane position held while lane-locked
+    pub lane_position: f32,
+    /// Start offset ahead of the common start line (meters)
+    pub stagger: f32,
+    /// Meters from the runner's own start until which it holds its lane
+    /// (`None` = free to drift from the gun)
+    pub locked_until: Option<f32>,
+}
+
+impl Default for LaneAssignment {
+    fn default() -> Self {
+        Self {
+            lane: 1,
+            lane_position: 1.0,
+            stagger: 0.0,
+            locked_until: None,
+        }
     }
 }
 
+/// Input from a human player
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PlayerInput {
+    /// Desired running speed (m/s, real time)
+    pub desired_speed: f32,
+    /// Lane change direction (-1 = inside, +1 = outside, 0 = hold)
+    pub lane_change: f32,
+}
+
+impl PlayerInput {
+    /// Fastest speed a player can ask for (m/s)
+    pub const MAX_SPEED: f32 = 12.0;
+
+    /// Clamp input to valid ranges
+    pub fn clamped(self) -> Self {
+        Self {
+            desired_speed: self.desired_speed.clamp(0.0, Self::MAX_SPEED),
+            lane_change: self.lane_change.clamp(-1.0, 1.0),
+        }
+    }
+}
+
+/// Who drives a runner
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum RunnerControl {
+    /// AI following its split times
+    #[default]
+    Ai,
+    /// Human player with their latest input
+    Player(PlayerInput),
+}
+
 /// Runner state flags
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct RunnerFlags {
     pub finished: bool,
     pub squished: bool,
+    /// Sitting in another runner's slipstream
+    pub drafting: bool,
 }
 
 /// Complete state for a single runner
@@ -55,6 +204,8 @@ pub struct RunnerState {
     pub id: u32,
     /// Runner name
     pub name: String,
+    /// Roster bib, if the runner is a roster athlete
+    pub bib: Option<u32>,
     /// Distance traveled along track (meters)
     pub distance: f32,
     /// Lane position (offset from inside edge)
@@ -67,38 +218,93 @@ pub struct RunnerState {
     pub animation_phase: f32,
     /// Stride multiplier for animation variation
     pub stride_multiplier: f32,
-    /// Split times for pacing
+    /// Split times for pacing (reference pace for players)
     pub split_times: SplitTimes,
+    /// Pacing strategy the splits were shaped by
+    pub strategy: PacingStrategy,
+    /// Where this runner's surges fall (meters, surgers only)
+    pub surge_offset: f32,
+    /// Random in-race form (surges, bad patches, recoveries)
+    pub form: Form,
+    /// AI or player control
+    pub control: RunnerControl,
+    /// Fatigue model state
+    pub energy: EnergySystem,
+    /// Start lane, stagger and lane lock
+    pub lane_assignment: LaneAssignment,
+    /// Lane-corrected finish distance (race distance + stagger)
+    pub finish_distance: f32,
+    /// Race time at each timing point passed so far
+    pub split_log: Vec<f32>,
     /// Status flags
     pub flags: RunnerFlags,
 }
 
 impl RunnerState {
-    /// Create a new runner with given finish time
-    pub fn new(id: u32, name: String, finish_time: f32) -> Self {
+    /// Create a new runner pacing to the given split times
+    pub fn new<R: Rng + ?Sized>(
+        id: u32,
+        name: String,
+        split_times: SplitTimes,
+        strategy: PacingStrategy,
+        rng: &mut R,
+    ) -> Self {
+        let finish_distance = split_times.race_distance();
+
         Self {
             id,
             name,
+            bib: None,
             distance: 0.0,
             lane_position: 1.0,
             current_speed: 0.0,
             target_speed: 0.0,
-            animation_phase: rand::random::<f32>(),
-            stride_multiplier: 0.85 + rand::random::<f32>() * 0.3,
-            split_times: SplitTimes::from_finish_time(finish_time),
+            animation_phase: rng.gen::<f32>(),
+            stride_multiplier: 0.85 + rng.gen::<f32>() * 0.3,
+            split_times,
+            strategy,
+            surge_offset: PacingStrategy::random_surge_offset(rng),
+            form: Form::default(),
+            control: RunnerControl::Ai,
+            energy: EnergySystem::None,
+            lane_assignment: LaneAssignment::default(),
+            finish_distance,
+            split_log: Vec::new(),
             flags: RunnerFlags::default(),
         }
     }
 
-    /// Reset runner to starting position
-    pub fn reset(&mut self, start_distance: f32, start_lane: f32) {
+    /// Reset runner to starting position in its assigned lane
+    pub fn reset<R: Rng + ?Sized>(&mut self, start_distance: f32, lane: LaneAssignment, rng: &mut R) {
         self.distance = start_distance;
-        self.lane_position = start_lane;
+        self.lane_position = lane.lane_position;
+        self.lane_assignment = lane;
+        self.finish_distance = self.split_times.race_distance() + lane.stagger;
         self.current_speed = 0.0;
         self.target_speed = 0.0;
-        self.animation_phase = rand::random::<f32>();
+        self.animation_phase = rng.gen::<f32>();
+        self.energy.reset();
+        self.form = Form::default();
+        self.split_log.clear();
         self.flags = RunnerFlags::default();
     }
+
+    /// Distance covered from the runner's own (staggered) start line
+    pub fn progress(&self) -> f32 {
+        self.distance - self.lane_assignment.stagger
+    }
+
+    /// Whether a human player drives this runner
+    pub fn is_player(&self) -> bool {
+        matches!(self.control, RunnerControl::Player(_))
+    }
+
+    /// Whether the runner still has to hold its assigned lane
+    pub fn is_lane_locked(&self) -> bool {
+        self.lane_assignment
+            .locked_until
+            .is_some_and(|until| self.progress() < until)
+    }
 }
 
 /// Runner simulation logic
@@ -109,9 +315,14 @@ impl Runner {
     const ACCELERATION_RATE: f32 = 2.0;
     const BASE_ANIMATION_SPEED: f32 = 5000.0 / 600.0;
     const COOLDOWN_FACTOR: f32 = 0.5;
+    /// Drafting lowers the effort of a given pace; modelled as holding
+    /// this much extra speed for the same effort
+    pub(crate) const DRAFT_SPEED_BONUS: f32 = 1.03;
     const DRIFT_LEFT_SPEED: f32 = 0.15;
     const MIN_LANE: f32 = 0.75;
     const MAX_LANE: f32 = 2.0;
+    /// How fast a player can change lanes (lane units per second)
+    const LANE_MOVE_SPEED: f32 = 1.5;
 
     /// Update a single runner for one tick
     pub fn update(
@@ -120,21 +331,34 @@ impl Runner {
         time_scale: f32,
         race_distance: f32,
     ) {
-        // Check if finished
-        if !state.flags.finished && state.distance >= race_distance {
+        // Check if finished (lane-corrected, so staggered lanes run the same distance)
+        if !state.flags.finished && state.distance >= state.finish_distance {
             state.flags.finished = true;
         }
 
         // Calculate target speed
-        if state.flags.finished {
+        let planned_speed = if state.flags.finished {
             let base_speed = state.split_times.get_target_speed(race_distance - 1.0, time_scale);
             state.target_speed = base_speed * Self::COOLDOWN_FACTOR;
+            None
         } else {
-            state.target_speed = state.split_times.get_target_speed(state.distance, time_scale);
-        }
+            let planned = state.split_times.get_target_speed(state.progress(), time_scale);
+            let desired = match state.control {
+                RunnerControl::Ai => {
+                    let strategy = state.strategy.speed_multiplier(state.progress(), state.surge_offset, state.flags.drafting);
+                    planned * strategy * state.form.multiplier
+                }
+                RunnerControl::Player(input) => input.desired_speed / time_scale,
+            };
+            state.target_speed = desired * state.energy.speed_multiplier();
+            if state.flags.drafting {
+                state.target_speed *= Self::DRAFT_SPEED_BONUS;
+            }
+            Some(planned)
+        };
 
         // Smooth acceleration
-        let accel = Self::ACCELERATION_RATE * delta;
+        let accel = Self::ACCELERATION_RATE * state.strategy.acceleration_multiplier() * delta;
         if state.current_speed < state.target_speed {
             state.current_speed = (state.current_speed + accel).min(state.target_speed);
         } else if state.current_speed > state.target_speed {
@@ -142,15 +366,33 @@ impl Runner {
         }
 
         // Move forward
-        state.distance += state.current_speed * delta;
+        let moved = state.current_speed * delta;
+        state.distance += moved;
+
+        // Spend energy relative to the planned pace
+        if let Some(planned) = planned_speed {
+            let intensity = state.current_speed / planned;
+            state.energy.update(moved, intensity, state.flags.drafting, state.progress(), race_distance);
+        }
 
         // Update animation phase
         let anim_scale = state.current_speed / Self::BASE_ANIMATION_SPEED;
         state.animation_phase += delta * anim_scale.max(0.3) * state.stride_multiplier;
         state.animation_phase %= 1.0;
 
-        // Lane drift toward inside
-        if state.lane_position > Self::MIN_LANE {
+        // Lane drift toward inside (lane-locked runners hold their lane,
+        // players steer while they hold a lane change)
+        let steering = match state.control {
+            RunnerControl::Player(input) if input.lane_change != 0.0 => input.lane_change,
+            _ => 0.0,
+        };
+
+        if state.is_lane_locked() {
+            state.lane_position = state.lane_assignment.lane_position;
+        } else if steering != 0.0 {
+            let target = state.lane_position + steering * Self::LANE_MOVE_SPEED * delta;
+            state.lane_position = target.clamp(Self::MIN_LANE, Self::MAX_LANE.max(state.lane_position));
+        } else if state.lane_position > Self::MIN_LANE {
             let drift = Self::DRIFT_LEFT_SPEED * delta * state.lane_position;
             state.lane_position = (state.lane_position - drift).max(Self::MIN_LANE);
         }
@@ -166,6 +408,34 @@ pub struct RunnerSnapshot {
     pub speed: f32,
     pub animation_phase: f32,
     pub finished: bool,
+    pub drafting: bool,
+    /// Energy display value (0-100): lactic acid level, stamina, or kick bar
+    pub energy: f32,
+    pub kicking: bool,
+    /// Driven by a human player
+    pub player: bool,
+    /// Assigned start lane
+    pub lane: u32,
+    /// Lane-corrected finish distance
+    pub finish_distance: f32,
+    /// Current place (1 = leading, 0 before the gun)
+    #[serde(default)]
+    pub place: u32,
+    /// Distance behind the leader (m)
+    #[serde(default)]
+    pub gap: f32,
+    /// Time behind the leader (race seconds)
+    #[serde(default)]
+    pub gap_time: f32,
+    /// Distance behind the runner one place ahead (m)
+    #[serde(default)]
+    pub interval: f32,
+    /// Time behind the runner one place ahead (race seconds)
+    #[serde(default)]
+    pub interval_time: f32,
+    /// World placement (only when the race is configured to include it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world: Option<TrackPosition>,
 }
 
 impl From<&RunnerState> for RunnerSnapshot {
@@ -177,6 +447,64 @@ impl From<&RunnerState> for RunnerSnapshot {
             speed: state.current_speed,
             animation_phase: state.animation_phase,
             finished: state.flags.finished,
+            drafting: state.flags.drafting,
+            energy: state.energy.display_value(),
+            kicking: state.energy.is_kicking(),
+            player: state.is_player(),
+            lane: state.lane_assignment.lane,
+            finish_distance: state.finish_distance,
+            place: 0,
+            gap: 0.0,
+            gap_time: 0.0,
+            interval: 0.0,
+            interval_time: 0.0,
+            world: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Default split points for a race distance
+    fn default_splits(distance: f32, finish_time: f32) -> SplitTimes {
+        let mut rng = StdRng::seed_from_u64(5);
+        SplitTimes::from_finish_time(
+            finish_time,
+            distance,
+            SplitInterval::for_distance(distance),
+            PacingStrategy::Even,
+            &mut rng,
+        )
+    }
+
+    #[test]
+    fn default_split_points() {
+        assert_eq!(default_splits(400.0, 50.0).distances, vec![400.0]);
+        assert_eq!(default_splits(1600.0, 240.0).distances, vec![400.0, 800.0, 1200.0, 1600.0]);
+        assert_eq!(
+            default_splits(10_000.0, 1800.0).distances,
+            (1..=10).map(|km| km as f32 * 1000.0).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn uneven_distance_ends_with_a_short_split() {
+        assert_eq!(default_splits(1500.0, 220.0).distances, vec![400.0, 800.0, 1200.0, 1500.0]);
+        assert_eq!(SplitTimes::split_distances(5000.0, SplitInterval::Mile.meters()).len(), 4);
+    }
+
+    #[test]
+    fn splits_rise_to_the_finish_time() {
+        for (distance, finish_time) in [(400.0, 50.0), (1600.0, 240.0), (10_000.0, 1800.0)] {
+            let splits = default_splits(distance, finish_time);
+            assert_eq!(splits.splits.len(), splits.distances.len());
+            assert!(splits.splits.windows(2).all(|pair| pair[1] > pair[0]));
+            assert_eq!(splits.splits.last().copied(), Some(finish_time));
+            assert_eq!(splits.race_distance(), distance);
         }
     }
 }
@@ -0,0 +1,322 @@
+//! Runtime - Simulation thread
+//!
+//! Owns a `GameServer` on a dedicated thread and ticks it at the server's
+//! tick rate, so the race keeps running between frontend calls. Commands
+//! arrive over a channel and every tick is pushed out as a `SimEvent`;
+//! nothing outside the thread ever locks the server.
+//!
+//! Frame subscribers also get every tick as a binary frame (see
+//! `SNAPSHOT_FORMAT.md`). Each subscription has its own encoder, so acks
+//! from one subscriber never change what another is sent. Event
+//! subscribers (such as the multiplayer host) see every `SimEvent` as well.
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::game_server::commentary::CommentaryLine;
+use crate::game_server::error::GameError;
+use crate::game_server::events::RaceEvent;
+use crate::game_server::race::{PlaceChange, RaceResult};
+use crate::game_server::simulation::{GameServer, GameState, TickResult};
+use crate::game_server::wire::SnapshotEncoder;
+
+/// Where the outcome of a control command is sent
+pub type Reply = Sender<Result<(), GameError>>;
+
+/// Receives binary frames on the simulation thread. Returns false once the
+/// receiver has gone, which ends the subscription.
+pub type FrameSink = Box<dyn FnMut(Vec<u8>) -> bool + Send>;
+
+/// Receives events on the simulation thread. Returns false once the
+/// receiver has gone, which ends the subscription.
+pub type EventSink = Box<dyn FnMut(&SimEvent) -> bool + Send>;
+
+/// Command for the simulation thread
+pub enum SimCommand {
+    /// Start the race countdown
+    Start(Reply),
+    /// Pause the simulation
+    Pause(Reply),
+    /// Resume the simulation
+    Resume(Reply),
+    /// Reset to idle state
+    Reset(Reply),
+    /// Run a closure against the server on the simulation thread
+    Call(Box<dyn FnOnce(&mut GameServer) + Send>),
+    /// Send every tick to the sink as a binary frame, starting with the
+    /// current state. Replies with the subscription ID.
+    SubscribeFrames(FrameSink, Sender<u32>),
+    /// A frame subscriber decoded frame `sequence`; `None` asks for a keyframe
+    AckFrame { subscription: u32, sequence: Option<u32> },
+    /// Stop sending frames to a subscriber
+    UnsubscribeFrames(u32),
+    /// Send every event to the sink as well. Replies with the subscription ID.
+    SubscribeEvents(EventSink, Sender<u32>),
+    /// Stop sending events to a subscriber
+    UnsubscribeEvents(u32),
+    /// Stop the thread
+    Shutdown,
+}
+
+/// Event pushed from the simulation thread
+#[derive(Debug, Clone, Serialize)]
+pub enum SimEvent {
+    /// Race state after a tick (or after a start/pause/resume/reset)
+    Snapshot(TickResult),
+    /// What happened during a tick, sent only when something did
+    Events(RaceEvents),
+    /// The game entered a new state (every state passed through, in order)
+    Status(GameState),
+    /// New commentary lines, oldest first
+    Commentary(Vec<CommentaryLine>),
+    /// Final results, once the race finishes
+    Results(Vec<RaceResult>),
+}
+
+/// Race events and place changes from a tick: the part of a `TickResult`
+/// that binary frames leave out
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceEvents {
+    pub events: Vec<RaceEvent>,
+    pub place_changes: Vec<PlaceChange>,
+}
+
+/// A binary frame feed and the encoder tracking what it acknowledged
+struct FrameSubscriber {
+    id: u32,
+    encoder: SnapshotEncoder,
+    sink: FrameSink,
+}
+
+impl FrameSubscriber {
+    /// Encode and send a tick, returning false if the receiver has gone
+    fn send(&mut self, result: &TickResult) -> bool {
+        (self.sink)(self.encoder.encode(result))
+    }
+}
+
+/// An event feed
+struct EventSubscriber {
+    id: u32,
+    sink: EventSink,
+}
+
+/// Handle to a `GameServer` running on its own thread
+pub struct SimulationThread {
+    commands: Sender<SimCommand>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SimulationThread {
+    /// Move `server` onto a new thread. `emit` is called on that thread for
+    /// every event.
+    pub fn spawn(server: GameServer, emit: impl FnMut(SimEvent) + Send + 'static) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("simulation".to_string())
+            .spawn(move || Self::run(server, receiver, emit))
+            .expect("failed to spawn simulation thread");
+
+        Self {
+            commands,
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue a command
+    pub fn send(&self, command: SimCommand) -> Result<(), GameError> {
+        self.commands.send(command).map_err(|_| GameError::Unavailable)
+    }
+
+    /// Send a control command and wait for the server to accept or reject it,
+    /// e.g. `sim.control(SimCommand::Start)`
+    pub fn control(&self, command: impl FnOnce(Reply) -> SimCommand) -> Result<(), GameError> {
+        let (reply, result) = mpsc::channel();
+        self.send(command(reply))?;
+        result.recv().map_err(|_| GameError::Unavailable)?
+    }
+
+    /// Run `f` against the server between ticks and wait for its result
+    pub fn call<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut GameServer) -> R + Send + 'static,
+    ) -> Result<R, GameError> {
+        let (reply, result) = mpsc::channel();
+        self.send(SimCommand::Call(Box::new(move |server| {
+            let _ = reply.send(f(server));
+        })))?;
+        result.recv().map_err(|_| GameError::Unavailable)
+    }
+
+    /// Receive every tick as a binary frame. Returns the subscription ID
+    /// to acknowledge frames with.
+    pub fn subscribe_frames(&self, sink: impl FnMut(Vec<u8>) -> bool + Send + 'static) -> Result<u32, GameError> {
+        let (reply, id) = mpsc::channel();
+        self.send(SimCommand::SubscribeFrames(Box::new(sink), reply))?;
+        id.recv().map_err(|_| GameError::Unavailable)
+    }
+
+    /// Record that a frame subscriber decoded frame `sequence` (`None` to
+    /// get a keyframe next)
+    pub fn ack_frame(&self, subscription: u32, sequence: Option<u32>) -> Result<(), GameError> {
+        self.send(SimCommand::AckFrame { subscription, sequence })
+    }
+
+    /// Stop sending frames to a subscriber
+    pub fn unsubscribe_frames(&self, subscription: u32) -> Result<(), GameError> {
+        self.send(SimCommand::UnsubscribeFrames(subscription))
+    }
+
+    /// Receive every event as well as the `emit` callback. Returns the
+    /// subscription ID.
+    pub fn subscribe_events(&self, sink: impl FnMut(&SimEvent) -> bool + Send + 'static) -> Result<u32, GameError> {
+        let (reply, id) = mpsc::channel();
+        self.send(SimCommand::SubscribeEvents(Box::new(sink), reply))?;
+        id.recv().map_err(|_| GameError::Unavailable)
+    }
+
+    /// Stop sending events to a subscriber
+    pub fn unsubscribe_events(&self, subscription: u32) -> Result<(), GameError> {
+        self.send(SimCommand::UnsubscribeEvents(subscription))
+    }
+
+    fn run(mut server: GameServer, commands: Receiver<SimCommand>, mut emit: impl FnMut(SimEvent)) {
+        let interval = Duration::from_secs_f32(server.fixed_delta());
+        let mut next_tick = Instant::now();
+        let mut subscribers: Vec<FrameSubscriber> = Vec::new();
+        let mut observers: Vec<EventSubscriber> = Vec::new();
+        let mut next_subscription = 0;
+
+        loop {
+            // Sleep until the next tick while running, otherwise until a command arrives
+            let command = if server.is_running() {
+                match commands.recv_timeout(next_tick.saturating_duration_since(Instant::now())) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            } else {
+                match commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => break,
+                }
+            };
+
+            let control = matches!(
+                command,
+                Some(SimCommand::Start(_) | SimCommand::Pause(_) | SimCommand::Resume(_) | SimCommand::Reset(_))
+            );
+
+            match command {
+                None => {
+                    // Skip missed ticks rather than bursting to catch up;
+                    // the server's accumulator already covers the lost time
+                    next_tick = (next_tick + interval).max(Instant::now());
+                    if let Some(result) = server.tick() {
+                        Self::publish(result, &mut subscribers, &mut observers, &mut emit);
+                    }
+                }
+                Some(SimCommand::Start(reply)) => {
+                    let _ = reply.send(server.start_race());
+                }
+                Some(SimCommand::Pause(reply)) => {
+                    let _ = reply.send(server.pause());
+                }
+                Some(SimCommand::Resume(reply)) => {
+                    let _ = reply.send(server.resume());
+                }
+                Some(SimCommand::Reset(reply)) => {
+                    let _ = reply.send(server.reset());
+                }
+                Some(SimCommand::Call(f)) => f(&mut server),
+                Some(SimCommand::SubscribeFrames(sink, reply)) => {
+                    let mut subscriber = FrameSubscriber { id: next_subscription, encoder: SnapshotEncoder::new(), sink };
+                    next_subscription += 1;
+                    let _ = reply.send(subscriber.id);
+                    if server.current_tick().map_or(true, |result| subscriber.send(&result)) {
+                        subscribers.push(subscriber);
+                    }
+                }
+                Some(SimCommand::AckFrame { subscription, sequence }) => {
+                    if let Some(subscriber) = subscribers.iter_mut().find(|s| s.id == subscription) {
+                        match sequence {
+                            Some(sequence) => subscriber.encoder.acknowledge(sequence),
+                            None => subscriber.encoder.reset(),
+                        }
+                    }
+                }
+                Some(SimCommand::UnsubscribeFrames(subscription)) => {
+                    subscribers.retain(|s| s.id != subscription);
+                }
+                Some(SimCommand::SubscribeEvents(sink, reply)) => {
+                    let _ = reply.send(next_subscription);
+                    observers.push(EventSubscriber { id: next_subscription, sink });
+                    next_subscription += 1;
+                }
+                Some(SimCommand::UnsubscribeEvents(subscription)) => {
+                    observers.retain(|o| o.id != subscription);
+                }
+                Some(SimCommand::Shutdown) => break,
+            }
+
+            // Push the new state straight away instead of waiting for a tick
+            if control {
+                next_tick = Instant::now() + interval;
+                if let Some(result) = server.current_tick() {
+                    Self::publish(result, &mut subscribers, &mut observers, &mut emit);
+                }
+            }
+
+            for state in server.take_transitions() {
+                Self::dispatch(SimEvent::Status(state), &mut observers, &mut emit);
+                if state == GameState::Results {
+                    if let Some(results) = server.get_results() {
+                        Self::dispatch(SimEvent::Results(results), &mut observers, &mut emit);
+                    }
+                }
+            }
+
+            let lines = server.take_new_commentary();
+            if !lines.is_empty() {
+                Self::dispatch(SimEvent::Commentary(lines), &mut observers, &mut emit);
+            }
+        }
+    }
+
+    /// Send a tick to every frame subscriber, dropping those that have gone,
+    /// then emit it
+    fn publish(
+        result: TickResult,
+        subscribers: &mut Vec<FrameSubscriber>,
+        observers: &mut Vec<EventSubscriber>,
+        emit: &mut impl FnMut(SimEvent),
+    ) {
+        subscribers.retain_mut(|subscriber| subscriber.send(&result));
+
+        if !result.events.is_empty() || !result.place_changes.is_empty() {
+            let events = RaceEvents {
+                events: result.events.clone(),
+                place_changes: result.place_changes.clone(),
+            };
+            Self::dispatch(SimEvent::Events(events), observers, emit);
+        }
+        Self::dispatch(SimEvent::Snapshot(result), observers, emit);
+    }
+
+    /// Show an event to every event subscriber, dropping those that have
+    /// gone, then emit it
+    fn dispatch(event: SimEvent, observers: &mut Vec<EventSubscriber>, emit: &mut impl FnMut(SimEvent)) {
+        observers.retain_mut(|observer| (observer.sink)(&event));
+        emit(event);
+    }
+}
+
+impl Drop for SimulationThread {
+    fn drop(&mut self) {
+        let _ = self.commands.send(SimCommand::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! Series - A season of races with points standings
+//!
+//! A series is a fixed list of races, possibly at different distances.
+//! Finishers score points by position from a scoring table and the
+//! standings carry over from race to race. Runners are matched between
+//! races by roster bib and human players by name; anonymous fillers from
+//! outside the roster don't score, so draw the fields from a roster to
+//! keep the same rivals all season.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::game_server::race::{RaceConfig, RaceResult};
+
+/// Series file format version
+pub const SERIES_VERSION: u32 = 1;
+
+/// How runners level on points are separated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TieBreak {
+    /// Most wins, then most second places, and so on
+    #[default]
+    Countback,
+    /// Better place in the most recent race either of them ran
+    LastRace,
+}
+
+/// One race on the calendar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesEvent {
+    pub name: String,
+    /// Meters
+    pub distance: f32,
+    /// Field size (the race default if not set)
+    #[serde(default)]
+    pub runner_count: Option<u32>,
+    /// Fixed seed for the race (random if not set)
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// A runner's finish in a series race
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesFinish {
+    pub name: String,
+    pub bib: Option<u32>,
+    /// Whether a human ran it
+    #[serde(default)]
+    pub player: bool,
+    pub position: u32,
+    pub finish_time: f32,
+    pub points: u32,
+}
+
+impl SeriesFinish {
+    /// Standings key: roster athletes by bib, players by name
+    fn key(&self) -> (Option<u32>, &str) {
+        match self.bib {
+            Some(bib) => (Some(bib), ""),
+            None => (None, &self.name),
+        }
+    }
+}
+
+/// A runner's line in the standings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Standing {
+    /// 1-based; runners the tie-break can't separate share a rank
+    pub rank: u32,
+    pub name: String,
+    pub bib: Option<u32>,
+    pub points: u32,
+    pub races: u32,
+    pub wins: u32,
+    /// Place in each completed race (`None` if they didn't run it)
+    pub positions: Vec<Option<u32>>,
+}
+
+/// Progress summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesInfo {
+    pub name: String,
+    pub race_count: u32,
+    pub races_completed: u32,
+    /// Next race to run (`None` once the series is over)
+    pub next: Option<SeriesEvent>,
+    pub scoring: Vec<u32>,
+    pub tie_break: TieBreak,
+}
+
+/// A season of races and its results so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Series {
+    pub version: u32,
+    pub name: String,
+    pub events: Vec<SeriesEvent>,
+    /// Points for 1st, 2nd, 3rd... (nothing beyond the end of the table)
+    pub scoring: Vec<u32>,
+    pub tie_break: TieBreak,
+    /// Scoring finishers of each completed race, in calendar order
+    pub completed: Vec<Vec<SeriesFinish>>,
+}
+
+impl Series {
+    /// Create a series with no races run yet
+    pub fn new(
+        name: String,
+        events: Vec<SeriesEvent>,
+        scoring: Option<Vec<u32>>,
+        tie_break: TieBreak,
+    ) -> Result<Self, String> {
+        if events.is_empty() {
+            return Err("A series needs at least one race".to_string());
+        }
+        if let Some(event) = events.iter().find(|e| !(e.distance.is_finite() && e.distance > 0.0)) {
+            return Err(format!(
+                "Race '{}' needs a positive distance in meters, got {}",
+                event.name, event.distance
+            ));
+        }
+        let field_sizes = 1..=RaceConfig::MAX_RUNNERS;
+        if let Some((event, count)) = events
+            .iter()
+            .find_map(|e| e.runner_count.filter(|n| !field_sizes.contains(n)).map(|n| (e, n)))
+        {
+            return Err(format!(
+                "Race '{}' needs between 1 and {} runners, got {}",
+                event.name,
+                RaceConfig::MAX_RUNNERS,
+                count
+            ));
+        }
+
+        Ok(Self {
+            version: SERIES_VERSION,
+            name,
+            events,
+            scoring: scoring.unwrap_or_else(Self::default_scoring),
+            tie_break,
+            completed: Vec::new(),
+        })
+    }
+
+    /// Points for the top eight
+    pub fn default_scoring() -> Vec<u32> {
+        vec![10, 8, 6, 5, 4, 3, 2, 1]
+    }
+
+    /// Points for a finishing position (1-based)
+    pub fn points_for(&self, position: u32) -> u32 {
+        position
+            .checked_sub(1)
+            .and_then(|i| self.scoring.get(i as usize))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Next race to run
+    pub fn next_event(&self) -> Option<&SeriesEvent> {
+        self.events.get(self.completed.len())
+    }
+
+    /// Races still to run, next first
+    pub fn upcoming(&self) -> &[SeriesEvent] {
+        &self.events[self.completed.len().min(self.events.len())..]
+    }
+
+    /// Whether every race has been run
+    pub fn is_complete(&self) -> bool {
+        self.completed.len() >= self.events.len()
+    }
+
+    /// Score the results of the next race. Anonymous fillers (neither a
+    /// roster athlete nor a player) aren't recorded at all, so they never
+    /// reach the standings, but they still take up the place they finished in.
+    pub fn record(&mut self, results: &[RaceResult]) -> Result<(), String> {
+        if self.is_complete() {
+            return Err("Series is already complete".to_string());
+        }
+
+        let finishes = results
+            .iter()
+            .filter(|r| r.bib.is_some() || r.player)
+            .map(|r| SeriesFinish {
+                name: r.runner_name.clone(),
+                bib: r.bib,
+                player: r.player,
+                position: r.position,
+                finish_time: r.finish_time,
+                points: self.points_for(r.position),
+            })
+            .collect();
+        self.completed.push(finishes);
+        Ok(())
+    }
+
+    /// Current standings, leader first
+    pub fn standings(&self) -> Vec<Standing> {
+        let race_count = self.completed.len();
+        let mut standings: Vec<Standing> = Vec::new();
+        let mut index: HashMap<(Option<u32>, &str), usize> = HashMap::new();
+
+        for (race, finishes) in self.completed.iter().enumerate() {
+            for finish in finishes {
+                let i = *index.entry(finish.key()).or_insert_with(|| {
+                    standings.push(Standing {
+                        rank: 0,
+                        name: finish.name.clone(),
+                        bib: finish.bib,
+                        points: 0,
+                        races: 0,
+                        wins: 0,
+                        positions: vec![None; race_count],
+                    });
+                    standings.len() - 1
+                });
+
+                let standing = &mut standings[i];
+                standing.points += finish.points;
+                standing.races += 1;
+                standing.wins += u32::from(finish.position == 1);
+                standing.positions[race] = Some(finish.position);
+            }
+        }
+
+        standings.sort_by(|a, b| self.compare(a, b).then_with(|| a.name.cmp(&b.name)));
+
+        for i in 0..standings.len() {
+            standings[i].rank = if i > 0 && self.compare(&standings[i - 1], &standings[i]) == Ordering::Equal {
+                standings[i - 1].rank
+            } else {
+                i as u32 + 1
+            };
+        }
+
+        standings
+    }
+
+    /// Order two standings, better first
+    fn compare(&self, a: &Standing, b: &Standing) -> Ordering {
+        b.points.cmp(&a.points).then_with(|| match self.tie_break {
+            TieBreak::Countback => {
+                let places = |s: &Standing| {
+                    let mut places: Vec<u32> = s.positions.iter().flatten().copied().collect();
+                    places.sort_unstable();
+                    places
+                };
+                // Fewer or worse places lose, e.g. [1, 3] beats [1, 4] and [1]
+                let (a, b) = (places(a), places(b));
+                a.iter()
+                    .zip(&b)
+                    .map(|(x, y)| x.cmp(y))
+                    .find(|o| o.is_ne())
+                    .unwrap_or_else(|| b.len().cmp(&a.len()))
+            }
+            TieBreak::LastRace => a
+                .positions
+                .iter()
+                .zip(&b.positions)
+                .rev()
+                .find(|(x, y)| x.is_some() || y.is_some())
+                .map_or(Ordering::Equal, |(x, y)| match (x, y) {
+                    (Some(x), Some(y)) => x.cmp(y),
+                    (Some(_), None) => Ordering::Less,
+                    _ => Ordering::Greater,
+                }),
+        })
+    }
+
+    /// Progress summary
+    pub fn info(&self) -> SeriesInfo {
+        SeriesInfo {
+            name: self.name.clone(),
+            race_count: self.events.len() as u32,
+            races_completed: self.completed.len() as u32,
+            next: self.next_event().cloned(),
+            scoring: self.scoring.clone(),
+            tie_break: self.tie_break,
+        }
+    }
+
+    /// Write the series to a JSON file, creating its directory if needed.
+    /// The file is written alongside and renamed into place, so a crash
+    /// mid-write leaves the previous save intact.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+
+        let mut file = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.flush()?;
+        file.get_ref().sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp, path)
+    }
+
+    /// Read a series from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let series: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+        if series.version != SERIES_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported series version {}", series.version),
+            ));
+        }
+
+        Ok(series)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, distance: f32) -> SeriesEvent {
+        SeriesEvent { name: name.to_string(), distance, runner_count: None, seed: None }
+    }
+
+    /// A finish by roster athlete `bib` (or an anonymous filler)
+    fn finish(bib: Option<u32>, name: &str, position: u32) -> RaceResult {
+        RaceResult {
+            runner_id: position,
+            runner_name: name.to_string(),
+            finish_time: 60.0 + position as f32,
+            position,
+            splits: Vec::new(),
+            strategy: Default::default(),
+            bib,
+            player: false,
+            flags: Vec::new(),
+        }
+    }
+
+    /// Two races scored 3-2-1 that leave A, B and C level on 4 points:
+    /// A and C with a win and a third, B with two seconds
+    fn level_series(tie_break: TieBreak) -> Series {
+        let events = vec![event("One", 1500.0), event("Two", 1500.0)];
+        let mut series = Series::new("Level".to_string(), events, Some(vec![3, 2, 1]), tie_break).unwrap();
+        series
+            .record(&[finish(Some(1), "A", 1), finish(Some(2), "B", 2), finish(Some(3), "C", 3)])
+            .unwrap();
+        series
+            .record(&[finish(Some(3), "C", 1), finish(Some(2), "B", 2), finish(Some(1), "A", 3)])
+            .unwrap();
+        series
+    }
+
+    fn table(series: &Series) -> Vec<(String, u32, u32)> {
+        series.standings().into_iter().map(|s| (s.name, s.points, s.rank)).collect()
+    }
+
+    #[test]
+    fn countback_ranks_wins_first_and_shares_exact_ties() {
+        let expected = [("A", 4, 1), ("C", 4, 1), ("B", 4, 3)].map(|(n, p, r)| (n.to_string(), p, r));
+        assert_eq!(table(&level_series(TieBreak::Countback)), expected);
+    }
+
+    #[test]
+    fn last_race_ranks_by_the_latest_finish() {
+        let expected = [("C", 4, 1), ("B", 4, 2), ("A", 4, 3)].map(|(n, p, r)| (n.to_string(), p, r));
+        assert_eq!(table(&level_series(TieBreak::LastRace)), expected);
+    }
+
+    #[test]
+    fn fillers_keep_their_place_but_never_score() {
+        let mut series = Series::new("Fillers".to_string(), vec![event("One", 800.0)], None, TieBreak::Countback).unwrap();
+        series
+            .record(&[finish(None, "Runner 1", 1), finish(Some(7), "G", 2)])
+            .unwrap();
+
+        assert_eq!(series.completed[0].len(), 1);
+        let standings = series.standings();
+        assert_eq!(standings.len(), 1);
+        assert_eq!((standings[0].points, standings[0].positions.clone()), (8, vec![Some(2)]));
+    }
+
+    #[test]
+    fn rejects_unusable_races() {
+        let create = |events| Series::new("Bad".to_string(), events, None, TieBreak::Countback);
+
+        assert!(create(Vec::new()).is_err());
+        for distance in [0.0, -400.0, f32::NAN, f32::INFINITY] {
+            assert!(create(vec![event("One", distance)]).is_err(), "distance {}", distance);
+        }
+        for runner_count in [0, RaceConfig::MAX_RUNNERS + 1] {
+            let events = vec![SeriesEvent { runner_count: Some(runner_count), ..event("One", 1500.0) }];
+            assert!(create(events).is_err(), "runner_count {}", runner_count);
+        }
+        assert!(create(vec![event("One", 1500.0)]).is_ok());
+    }
+}
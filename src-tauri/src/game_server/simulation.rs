@@ -3,20 +3,23 @@
 //! Manages the game server state, handles tick updates, and
 //! provides the interface for Tauri commands.
 
+use std::fs;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
 use serde::{Deserialize, Serialize};
-use crate::game_server::race::{Race, RaceConfig, RaceSnapshot, RaceStatus, RaceResult};
-
-/// Game state for the local AI mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum GameState {
-    Idle,
-    Loading,
-    Ready,
-    Racing,
-    Results,
-}
+use crate::game_server::commentary::{CommentaryLine, Commentator, Phrasebook};
+use crate::game_server::error::GameError;
+use crate::game_server::events::RaceEvent;
+use crate::game_server::race::{Entrant, PlaceChange, PlayerConfig, Race, RaceConfig, RaceSnapshot, RaceStatus, RaceResult, RunnerSplits};
+use crate::game_server::replay::{Replay, ReplayInfo, ReplayPlayer, ReplayRecorder};
+use crate::game_server::results::ResultsDb;
+use crate::game_server::roster::Roster;
+use crate::game_server::runner::PlayerInput;
+use crate::game_server::series::{Series, SeriesEvent};
+use crate::game_server::state::{GameCommand, StateMachine};
+
+pub use crate::game_server::state::GameState;
 
 /// Server statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,82 +30,243 @@ pub struct ServerStats {
     pub game_state: GameState,
 }
 
+/// Result of a single server tick
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickResult {
+    /// Race state after the last fixed step
+    pub snapshot: RaceSnapshot,
+    /// Fraction of a fixed step left in the accumulator (0-1), for render interpolation
+    pub alpha: f32,
+    /// Number of fixed steps simulated this tick
+    pub steps: u32,
+    /// What happened during those steps, oldest first
+    #[serde(default)]
+    pub events: Vec<RaceEvent>,
+    /// Runners whose place changed during those steps
+    #[serde(default)]
+    pub place_changes: Vec<PlaceChange>,
+}
+
 /// Main game server
 pub struct GameServer {
-    /// Current game state
-    state: GameState,
+    /// Current game and race state
+    machine: StateMachine,
     /// Active race (if any)
     race: Option<Race>,
-    /// Target tick rate (ticks per second)
+    /// Simulation rate (fixed steps per second)
     tick_rate: f32,
     /// Last tick timestamp
     last_tick: Instant,
+    /// Wall-clock time not yet consumed by fixed steps (seconds)
+    accumulator: f32,
     /// Accumulated tick time for averaging
     tick_times: Vec<f32>,
-    /// Whether the server is running
-    running: bool,
+    /// Whether races are recorded when they start
+    record_replays: bool,
+    /// Recording of the active race
+    recorder: Option<ReplayRecorder>,
+    /// Most recently completed recording
+    last_replay: Option<Replay>,
+    /// Replay being played back
+    playback: Option<ReplayPlayer>,
+    /// Loaded athlete roster
+    roster: Option<Roster>,
+    /// Bibs picked for the next roster race (a random draw if empty)
+    selected: Vec<u32>,
+    /// Series in progress
+    series: Option<Series>,
+    /// Where the series is saved after every race
+    series_path: Option<PathBuf>,
+    /// Whether the active race counts towards the series
+    series_race: bool,
+    /// Where finished races are stored
+    results_db: Option<ResultsDb>,
+    /// Turns race events into commentary
+    commentator: Commentator,
+    /// Commentary of the active race, oldest first
+    commentary: Vec<CommentaryLine>,
+    /// Commentary lines already handed out by `take_new_commentary`
+    commentary_sent: usize,
 }
 
 impl GameServer {
+    /// Longest wall-clock gap fed into the accumulator per tick (seconds).
+    /// Anything beyond this (stalled webview, breakpoint) is dropped.
+    const MAX_FRAME_TIME: f32 = 0.25;
+
+    /// Fixed steps per second unless set otherwise
+    pub const DEFAULT_TICK_RATE: f32 = 60.0;
+    /// Rates `with_tick_rate` accepts (steps per second)
+    pub const TICK_RATES: std::ops::RangeInclusive<f32> = 1.0..=1000.0;
+
     /// Create a new game server
     pub fn new() -> Self {
+        Self::build(Self::DEFAULT_TICK_RATE)
+    }
+
+    /// Create a new game server simulating at the given rate
+    pub fn with_tick_rate(tick_rate: f32) -> Result<Self, GameError> {
+        if !Self::TICK_RATES.contains(&tick_rate) {
+            return Err(GameError::config(
+                "tick_rate",
+                format!(
+                    "must be between {} and {} steps per second, got {}",
+                    Self::TICK_RATES.start(),
+                    Self::TICK_RATES.end(),
+                    tick_rate
+                ),
+            ));
+        }
+        Ok(Self::build(tick_rate))
+    }
+
+    fn build(tick_rate: f32) -> Self {
         Self {
-            state: GameState::Idle,
+            machine: StateMachine::new(),
             race: None,
-            tick_rate: 60.0,
+            tick_rate,
             last_tick: Instant::now(),
+            accumulator: 0.0,
             tick_times: Vec::with_capacity(60),
-            running: false,
+            record_replays: false,
+            recorder: None,
+            last_replay: None,
+            playback: None,
+            roster: None,
+            selected: Vec::new(),
+            series: None,
+            series_path: None,
+            series_race: false,
+            results_db: None,
+            commentator: Commentator::default(),
+            commentary: Vec::new(),
+            commentary_sent: 0,
         }
     }
 
-    /// Initialize a new race with given config
-    pub fn init_race(&mut self, config: RaceConfig) {
-        self.state = GameState::Loading;
+    /// Sim-time length of one fixed step (seconds)
+    pub fn fixed_delta(&self) -> f32 {
+        1.0 / self.tick_rate
+    }
+
+    /// Initialize a new race with given config. An invalid config leaves
+    /// the current race untouched.
+    pub fn init_race(&mut self, config: RaceConfig) -> Result<(), GameError> {
+        self.machine.check(GameCommand::InitRace)?;
+        config.validate()?;
 
+        self.transition(GameState::Loading)?;
         let mut race = Race::new(config);
         race.generate_runners();
         race.setup_starting_positions();
 
+        self.machine.set_race_status(race.status)?;
         self.race = Some(race);
-        self.state = GameState::Ready;
+        self.transition(GameState::Ready)
     }
 
     /// Start the race countdown
-    pub fn start_race(&mut self) {
-        if let Some(race) = &mut self.race {
-            race.start_countdown();
-            self.state = GameState::Racing;
-            self.running = true;
+    pub fn start_race(&mut self) -> Result<(), GameError> {
+        if self.machine.state() == GameState::Idle {
+            return Err(GameError::NoRace);
+        }
+        self.machine.check(GameCommand::StartRace)?;
+        if self.race.as_ref().map_or(0, |r| r.runners.len()) == 0 {
+            return Err(GameError::NoRunners);
+        }
+
+        self.transition(GameState::Racing)
+    }
+
+    /// Move to `to`, running the exit hook of the state left and the enter
+    /// hook of the new one
+    fn transition(&mut self, to: GameState) -> Result<(), GameError> {
+        let from = self.machine.transition(to)?;
+        self.on_exit(from);
+        self.on_enter(from, to)
+    }
+
+    fn on_exit(&mut self, from: GameState) {
+        if from == GameState::Paused {
+            // Don't feed the time spent paused into the accumulator
             self.last_tick = Instant::now();
+            self.accumulator = 0.0;
         }
     }
 
-    /// Perform a single simulation tick
-    pub fn tick(&mut self) -> Option<RaceSnapshot> {
-        if !self.running {
-            return self.race.as_ref().map(|r| r.get_snapshot());
+    fn on_enter(&mut self, from: GameState, to: GameState) -> Result<(), GameError> {
+        match to {
+            GameState::Idle => {
+                self.race = None;
+                self.accumulator = 0.0;
+                self.tick_times.clear();
+                self.playback = None;
+                self.recorder = None;
+                self.series_race = false;
+                self.clear_commentary();
+            }
+            GameState::Loading => {
+                self.playback = None;
+                self.recorder = None;
+                self.series_race = false;
+                self.clear_commentary();
+            }
+            GameState::Racing if from == GameState::Ready => {
+                let race = self.race.as_mut().ok_or(GameError::NoRace)?;
+                race.start_countdown();
+                self.machine.set_race_status(race.status)?;
+                if self.record_replays {
+                    self.recorder = Some(ReplayRecorder::new(race, ReplayRecorder::DEFAULT_FRAME_INTERVAL));
+                }
+                self.last_tick = Instant::now();
+                self.accumulator = 0.0;
+            }
+            GameState::Replay if from == GameState::Idle => self.last_tick = Instant::now(),
+            GameState::Results => self.finish_race(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Perform a simulation tick, advancing the race by as many fixed
+    /// steps as the wall-clock time since the last tick covers
+    pub fn tick(&mut self) -> Option<TickResult> {
+        if self.playback.is_some() {
+            return self.tick_playback();
+        }
+
+        if !self.is_running() {
+            return self.race.as_ref().map(|r| TickResult {
+                snapshot: r.get_snapshot(),
+                alpha: 0.0,
+                steps: 0,
+                events: Vec::new(),
+                place_changes: Vec::new(),
+            });
         }
 
         let now = Instant::now();
-        let delta = now.duration_since(self.last_tick).as_secs_f32();
+        let frame_time = now.duration_since(self.last_tick).as_secs_f32();
         self.last_tick = now;
+        self.advance(frame_time)
+    }
+
+    /// Run the fixed steps covered by `frame_time` more seconds of wall-clock
+    /// time, carrying the remainder over to the next tick
+    fn advance(&mut self, frame_time: f32) -> Option<TickResult> {
+        self.accumulator += frame_time.min(Self::MAX_FRAME_TIME);
 
         // Track tick timing
         let tick_start = Instant::now();
 
-        // Update race
-        if let Some(race) = &mut self.race {
-            race.update(delta);
+        let fixed_delta = self.fixed_delta();
+        let mut steps = 0;
 
-            // Check for state transitions
-            match race.status {
-                RaceStatus::Finished => {
-                    self.state = GameState::Results;
-                    self.running = false;
-                }
-                _ => {}
-            }
+        // Update race in fixed increments
+        while self.is_running() && self.accumulator >= fixed_delta {
+            self.accumulator -= fixed_delta;
+            self.step(fixed_delta);
+            steps += 1;
         }
 
         // Record tick time
@@ -112,19 +276,369 @@ impl GameServer {
             self.tick_times.remove(0);
         }
 
-        self.race.as_ref().map(|r| r.get_snapshot())
+        let alpha = self.accumulator / fixed_delta;
+        let race = self.race.as_mut()?;
+        let events = race.take_events();
+        let place_changes = race.take_place_changes();
+        self.commentary.extend(self.commentator.comment(race, &events));
+
+        Some(TickResult {
+            snapshot: race.get_snapshot(),
+            alpha,
+            steps,
+            events,
+            place_changes,
+        })
+    }
+
+    /// Advance the race by exactly one step of `delta` sim-seconds
+    fn step(&mut self, delta: f32) {
+        let Some(race) = &mut self.race else { return };
+        race.update(delta);
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(delta, race);
+        }
+
+        // Check for state transitions
+        let status = race.status;
+        let moved = self.machine.set_race_status(status).and_then(|_| {
+            if status == RaceStatus::Finished {
+                self.transition(GameState::Results)
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = moved {
+            log::warn!("Race state out of sync: {}", e);
+        }
+    }
+
+    /// Store and score the race that just finished
+    fn finish_race(&mut self) {
+        self.accumulator = 0.0;
+        if let Some(recorder) = self.recorder.take() {
+            self.last_replay = Some(recorder.finish());
+        }
+
+        let Some(race) = &mut self.race else { return };
+        if let Some(db) = &self.results_db {
+            // Series races count as a meet named after the series
+            let meet = self.series.as_ref().filter(|_| self.series_race).map(|s| s.name.as_str());
+            if let Err(e) = db.record_race(race, meet) {
+                log::warn!("Failed to store race results: {}", e);
+            }
+        }
+        if std::mem::take(&mut self.series_race) {
+            if let Some(series) = &mut self.series {
+                if let Err(e) = series.record(&race.finish_order) {
+                    log::warn!("Failed to score series race: {}", e);
+                }
+            }
+            self.save_series();
+        }
+    }
+
+    /// Advance replay playback by the wall-clock time since the last tick
+    fn tick_playback(&mut self) -> Option<TickResult> {
+        let playback = self.playback.as_mut()?;
+
+        let now = Instant::now();
+        let frame_time = now.duration_since(self.last_tick).as_secs_f32();
+        self.last_tick = now;
+
+        let steps = if self.machine.state().is_running() {
+            playback.advance(frame_time.min(Self::MAX_FRAME_TIME))
+        } else {
+            0
+        };
+        let result = TickResult {
+            snapshot: playback.snapshot(),
+            alpha: playback.alpha(),
+            steps,
+            events: Vec::new(),
+            place_changes: Vec::new(),
+        };
+
+        // Hold at the end until the user seeks back or resumes
+        if playback.is_finished() && self.machine.state() == GameState::Replay {
+            if let Err(e) = self.transition(GameState::Paused) {
+                log::warn!("Replay state out of sync: {}", e);
+            }
+        }
+        Some(result)
+    }
+
+    /// Commentary of the active race, optionally only lines after race time `since`
+    pub fn get_commentary(&self, since: Option<f32>) -> Vec<CommentaryLine> {
+        self.commentary
+            .iter()
+            .filter(|line| since.map_or(true, |t| line.time > t))
+            .cloned()
+            .collect()
+    }
+
+    /// Commentary lines said since the last call, oldest first
+    pub fn take_new_commentary(&mut self) -> Vec<CommentaryLine> {
+        let lines = self.commentary[self.commentary_sent..].to_vec();
+        self.commentary_sent = self.commentary.len();
+        lines
+    }
+
+    /// Use a different phrasebook from the next line on
+    pub fn set_phrasebook(&mut self, book: Phrasebook) {
+        self.commentator.set_phrasebook(book);
+    }
+
+    /// Drop the commentary of the last race
+    fn clear_commentary(&mut self) {
+        self.commentator.reset();
+        self.commentary.clear();
+        self.commentary_sent = 0;
+    }
+
+    /// The current state as a tick result, without advancing the race or
+    /// taking its events (those only go out with real ticks)
+    pub fn current_tick(&self) -> Option<TickResult> {
+        let (snapshot, alpha) = match &self.playback {
+            Some(playback) => (playback.snapshot(), playback.alpha()),
+            None => (self.race.as_ref()?.get_snapshot(), self.accumulator / self.fixed_delta()),
+        };
+        Some(TickResult {
+            snapshot,
+            alpha,
+            steps: 0,
+            events: Vec::new(),
+            place_changes: Vec::new(),
+        })
     }
 
     /// Get current race snapshot
     pub fn get_snapshot(&self) -> Option<RaceSnapshot> {
+        if let Some(playback) = &self.playback {
+            return Some(playback.snapshot());
+        }
         self.race.as_ref().map(|r| r.get_snapshot())
     }
 
+    /// Record races from their start. Takes effect from the next `start_race`.
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.record_replays = enabled;
+    }
+
+    /// The replay of the race in progress, or of the last recorded race
+    pub fn get_replay(&self) -> Option<&Replay> {
+        self.recorder
+            .as_ref()
+            .map(|r| r.replay())
+            .or(self.last_replay.as_ref())
+    }
+
+    /// Load a replay and start playing it back. Replaces any active race.
+    pub fn load_replay(&mut self, replay: Replay) -> Result<ReplayInfo, GameError> {
+        self.machine.check(GameCommand::LoadReplay)?;
+        self.reset()?;
+
+        let playback = ReplayPlayer::new(replay);
+        let info = playback.info();
+        self.playback = Some(playback);
+        self.transition(GameState::Replay)?;
+        Ok(info)
+    }
+
+    /// Jump to a position in the loaded replay (seconds)
+    pub fn seek_replay(&mut self, time: f32) -> Result<(), GameError> {
+        self.machine.check(GameCommand::SeekReplay)?;
+        if !time.is_finite() {
+            return Err(GameError::config("time", format!("must be a number of seconds, got {}", time)));
+        }
+        self.playback.as_mut().ok_or(GameError::NoReplay)?.seek(time);
+        Ok(())
+    }
+
+    /// Set the replay playback rate of the loaded replay
+    pub fn set_replay_speed(&mut self, speed: f32) -> Result<(), GameError> {
+        self.machine.check(GameCommand::SetReplaySpeed)?;
+        if !speed.is_finite() {
+            return Err(GameError::config("speed", format!("must be a finite rate, got {}", speed)));
+        }
+        self.playback.as_mut().ok_or(GameError::NoReplay)?.set_speed(speed);
+        Ok(())
+    }
+
+    /// Playback position and status of the loaded replay
+    pub fn get_replay_info(&self) -> Option<ReplayInfo> {
+        self.playback.as_ref().map(|p| p.info())
+    }
+
+    /// Apply player input to the active race
+    pub fn set_player_input(&mut self, input: PlayerInput) -> Result<(), GameError> {
+        self.machine.check(GameCommand::SetPlayerInput)?;
+        let race = self.race.as_mut().ok_or(GameError::NoRace)?;
+        if race.set_player_input(input) {
+            Ok(())
+        } else {
+            Err(GameError::NoPlayer)
+        }
+    }
+
+    /// Add a player to the race before it starts. Returns the runner ID,
+    /// or `None` if no race is waiting to start.
+    pub fn add_player(&mut self, player: &PlayerConfig) -> Option<u32> {
+        if self.machine.state() != GameState::Ready {
+            return None;
+        }
+
+        let race = self.race.as_mut()?;
+        Some(race.join_player(player))
+    }
+
+    /// Apply input to a specific player runner. Returns false if it is not a player.
+    pub fn set_runner_input(&mut self, id: u32, input: PlayerInput) -> bool {
+        self.race.as_mut().is_some_and(|r| r.set_runner_input(id, input))
+    }
+
+    /// Hand a player's runner over to the AI
+    pub fn release_player(&mut self, id: u32) {
+        if let Some(race) = &mut self.race {
+            race.release_player(id);
+        }
+    }
+
     /// Get race results
     pub fn get_results(&self) -> Option<Vec<RaceResult>> {
+        if let Some(playback) = &self.playback {
+            return Some(playback.replay().results.clone());
+        }
         self.race.as_ref().map(|r| r.finish_order.clone())
     }
 
+    /// Actual splits recorded so far, for one runner or (with `None`) every runner
+    pub fn get_splits(&self, runner_id: Option<u32>) -> Vec<RunnerSplits> {
+        match (&self.race, runner_id) {
+            (Some(race), Some(id)) => race.get_splits(id).into_iter().collect(),
+            (Some(race), None) => race.get_all_splits(),
+            (None, _) => Vec::new(),
+        }
+    }
+
+    /// Replace the loaded roster, clearing any selection
+    pub fn set_roster(&mut self, roster: Roster) {
+        self.roster = Some(roster);
+        self.selected.clear();
+    }
+
+    /// Get the loaded roster
+    pub fn get_roster(&self) -> Option<&Roster> {
+        self.roster.as_ref()
+    }
+
+    /// Pick which roster athletes run the next roster race
+    pub fn select_athletes(&mut self, bibs: Vec<u32>) -> Result<(), GameError> {
+        let roster = self.roster.as_ref().ok_or(GameError::NoRoster)?;
+        if let Some(&bib) = bibs.iter().find(|&&bib| roster.get(bib).is_none()) {
+            return Err(GameError::UnknownAthlete(bib));
+        }
+        self.selected = bibs;
+        Ok(())
+    }
+
+    /// Fill `config` with the loaded roster and selection
+    pub fn apply_roster(&self, config: &mut RaceConfig) -> Result<(), GameError> {
+        let roster = self.roster.as_ref().ok_or(GameError::NoRoster)?;
+        config.roster = Some(roster.clone());
+        config.entrants = self.selected.clone();
+        Ok(())
+    }
+
+    /// Who is running in the active race
+    pub fn get_entrants(&self) -> Vec<Entrant> {
+        self.race.as_ref().map(|r| r.get_entrants()).unwrap_or_default()
+    }
+
+    /// Store every finished race in the results database at `path`
+    pub fn open_results(&mut self, path: PathBuf) {
+        if let Some(dir) = path.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                log::warn!("Failed to create {}: {}", dir.display(), e);
+            }
+        }
+        match ResultsDb::open(&path) {
+            Ok(db) => self.results_db = Some(db),
+            Err(e) => log::warn!("Failed to open results database {}: {}", path.display(), e),
+        }
+    }
+
+    /// Get the results database, if one is open
+    pub fn get_results_db(&self) -> Option<&ResultsDb> {
+        self.results_db.as_ref()
+    }
+
+    /// Use `path` to persist the series, picking up the series saved there if any
+    pub fn open_series(&mut self, path: PathBuf) {
+        if path.exists() {
+            match Series::load(&path) {
+                Ok(series) => {
+                    log::info!("Resumed series '{}' from {}", series.name, path.display());
+                    self.series = Some(series);
+                }
+                Err(e) => log::warn!("Failed to load series from {}: {}", path.display(), e),
+            }
+        }
+        self.series_path = Some(path);
+    }
+
+    /// Start a new series, replacing any in progress
+    pub fn start_series(&mut self, series: Series) {
+        self.series = Some(series);
+        self.series_race = false;
+        self.save_series();
+    }
+
+    /// Get the series in progress
+    pub fn get_series(&self) -> Option<&Series> {
+        self.series.as_ref()
+    }
+
+    /// Abandon the series in progress and delete its saved copy
+    pub fn end_series(&mut self) {
+        self.series = None;
+        self.series_race = false;
+        if let Some(path) = &self.series_path {
+            if path.exists() {
+                if let Err(e) = fs::remove_file(path) {
+                    log::warn!("Failed to delete series file {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    /// Set up the next series race on top of `config`, drawing the field
+    /// from the loaded roster if there is one
+    pub fn init_series_race(&mut self, mut config: RaceConfig) -> Result<SeriesEvent, GameError> {
+        let series = self.series.as_ref().ok_or(GameError::NoSeries)?;
+        let event = series.next_event().ok_or(GameError::SeriesComplete)?.clone();
+
+        config.distance = event.distance;
+        config.runner_count = event.runner_count.unwrap_or(config.runner_count);
+        config.seed = event.seed;
+        if self.roster.is_some() {
+            self.apply_roster(&mut config)?;
+        }
+
+        self.init_race(config)?;
+        self.series_race = true;
+        Ok(event)
+    }
+
+    fn save_series(&self) {
+        if let (Some(series), Some(path)) = (&self.series, &self.series_path) {
+            if let Err(e) = series.save(path) {
+                log::warn!("Failed to save series to {}: {}", path.display(), e);
+            }
+        }
+    }
+
     /// Get server statistics
     pub fn get_stats(&self) -> ServerStats {
         let avg_tick_time = if self.tick_times.is_empty() {
@@ -137,39 +651,67 @@ impl GameServer {
             tick_rate: self.tick_rate,
             avg_tick_time_ms: avg_tick_time,
             runner_count: self.race.as_ref().map(|r| r.runners.len() as u32).unwrap_or(0),
-            game_state: self.state,
+            game_state: self.machine.state(),
         }
     }
 
     /// Get current game state
     pub fn get_state(&self) -> GameState {
-        self.state
+        self.machine.state()
     }
 
-    /// Reset to idle state
-    pub fn reset(&mut self) {
-        self.state = GameState::Idle;
-        self.race = None;
-        self.running = false;
-        self.tick_times.clear();
+    /// Commands the server will accept right now
+    pub fn allowed_commands(&self) -> Vec<GameCommand> {
+        GameCommand::ALL
+            .into_iter()
+            .filter(|&command| self.machine.check(command).is_ok())
+            .filter(|&command| match command {
+                GameCommand::StartRace => self.race.as_ref().is_some_and(|r| !r.runners.is_empty()),
+                GameCommand::InitSeriesRace => self.series.as_ref().is_some_and(|s| !s.is_complete()),
+                GameCommand::SetPlayerInput => self.race.as_ref().is_some_and(|r| r.get_player().is_some()),
+                GameCommand::SaveReplay => self.get_replay().is_some(),
+                GameCommand::SeekReplay | GameCommand::SetReplaySpeed => self.playback.is_some(),
+                _ => true,
+            })
+            .collect()
     }
 
-    /// Pause the simulation
-    pub fn pause(&mut self) {
-        self.running = false;
+    /// Game states entered since the last call, oldest first. Includes
+    /// passing states like `Loading` that `get_state` never sees.
+    pub fn take_transitions(&mut self) -> Vec<GameState> {
+        self.machine.take_entered()
     }
 
-    /// Resume the simulation
-    pub fn resume(&mut self) {
-        if self.state == GameState::Racing {
-            self.running = true;
-            self.last_tick = Instant::now();
+    /// Get the seed of the active race
+    pub fn get_seed(&self) -> Option<u64> {
+        if let Some(playback) = &self.playback {
+            return Some(playback.replay().seed);
         }
+        self.race.as_ref().map(|r| r.seed)
+    }
+
+    /// Reset to idle state
+    pub fn reset(&mut self) -> Result<(), GameError> {
+        self.machine.check(GameCommand::ResetRace)?;
+        self.transition(GameState::Idle)
+    }
+
+    /// Pause the race or replay
+    pub fn pause(&mut self) -> Result<(), GameError> {
+        self.machine.check(GameCommand::PauseRace)?;
+        self.transition(GameState::Paused)
+    }
+
+    /// Resume the paused race or replay
+    pub fn resume(&mut self) -> Result<(), GameError> {
+        self.machine.check(GameCommand::ResumeRace)?;
+        let to = if self.playback.is_some() { GameState::Replay } else { GameState::Racing };
+        self.transition(to)
     }
 
     /// Check if server is running
     pub fn is_running(&self) -> bool {
-        self.running
+        self.machine.state().is_running()
     }
 }
 
@@ -186,3 +728,64 @@ pub type SharedGameServer = Arc<RwLock<GameServer>>;
 pub fn create_shared_server() -> SharedGameServer {
     Arc::new(RwLock::new(GameServer::new()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Server with a seeded 400 m race counting down
+    fn started_server() -> GameServer {
+        let mut server = GameServer::new();
+        server
+            .init_race(RaceConfig {
+                distance: 400.0,
+                runner_count: 6,
+                time_scale: 1.0,
+                seed: Some(3),
+                ..RaceConfig::default()
+            })
+            .unwrap();
+        server.start_race().unwrap();
+        server
+    }
+
+    /// Run a race to the finish feeding it `frames` (seconds) in turn,
+    /// returning the results as JSON
+    fn results_with_frames(frames: &[f32]) -> String {
+        let mut server = started_server();
+        for frame_time in frames.iter().cycle().take(100_000) {
+            if server.get_state() == GameState::Results {
+                break;
+            }
+            server.advance(*frame_time);
+        }
+        assert_eq!(server.get_state(), GameState::Results);
+        serde_json::to_string(&server.get_results()).unwrap()
+    }
+
+    #[test]
+    fn frame_sizes_dont_change_results() {
+        let steady = results_with_frames(&[1.0 / 60.0]);
+        assert_eq!(results_with_frames(&[0.005, 0.031, 0.2, 1.0 / 60.0, 0.09]), steady);
+        assert_eq!(results_with_frames(&[1.0 / 144.0]), steady);
+    }
+
+    #[test]
+    fn reset_returns_to_idle_from_a_race() {
+        let mut server = started_server();
+        assert_eq!(server.reset(), Ok(()));
+        assert_eq!(server.get_state(), GameState::Idle);
+        assert!(server.get_snapshot().is_none());
+        // Resetting while idle is fine too
+        assert_eq!(server.reset(), Ok(()));
+    }
+
+    #[test]
+    fn partial_steps_carry_over() {
+        let mut server = started_server();
+        assert_eq!(server.advance(0.01).unwrap().steps, 0);
+        assert_eq!(server.advance(0.01).unwrap().steps, 1);
+        // Longer gaps only count up to MAX_FRAME_TIME
+        assert_eq!(server.advance(10.0).unwrap().steps, 15);
+    }
+}
@@ -0,0 +1,321 @@
+//! State - Game and race state machine
+//!
+//! `GameState` and `RaceStatus` only change through the transition tables
+//! here. `GameServer` runs its enter/exit hooks around every transition and
+//! uses `GameCommand::allowed_in` to decide which commands it accepts.
+
+use serde::{Deserialize, Serialize};
+use crate::game_server::error::GameError;
+
+/// Game state for the local AI mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameState {
+    Idle,
+    /// Building a race from its config
+    Loading,
+    Ready,
+    Racing,
+    /// A race or replay on hold
+    Paused,
+    Results,
+    /// Playing back a recorded race
+    Replay,
+}
+
+impl GameState {
+    /// Whether the machine may move from `self` to `to`
+    pub fn can_transition_to(self, to: GameState) -> bool {
+        use GameState::*;
+
+        match (self, to) {
+            // A new race or a reset can replace whatever is going on
+            (Loading, Loading) => false,
+            (_, Loading | Idle) => true,
+            (Loading, Ready) => true,
+            (Ready, Racing) => true,
+            (Racing, Paused | Results) => true,
+            (Paused, Racing | Replay) => true,
+            (Idle, Replay) => true,
+            (Replay, Paused) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the simulation advances on every tick in this state
+    pub fn is_running(self) -> bool {
+        matches!(self, GameState::Racing | GameState::Replay)
+    }
+}
+
+/// Race status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RaceStatus {
+    NotStarted,
+    Countdown,
+    Racing,
+    Finished,
+}
+
+impl RaceStatus {
+    /// Whether a race may move from `self` to `to`. Races only ever go forward.
+    pub fn can_transition_to(self, to: RaceStatus) -> bool {
+        matches!(
+            (self, to),
+            (RaceStatus::NotStarted, RaceStatus::Countdown)
+                | (RaceStatus::Countdown, RaceStatus::Racing)
+                | (RaceStatus::Racing, RaceStatus::Finished)
+        )
+    }
+}
+
+/// Frontend commands whose availability depends on the game state.
+/// Serialized with the name of the matching Tauri command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameCommand {
+    InitRace,
+    InitSeriesRace,
+    StartRace,
+    PauseRace,
+    ResumeRace,
+    ResetRace,
+    SetPlayerInput,
+    SaveReplay,
+    LoadReplay,
+    SeekReplay,
+    SetReplaySpeed,
+}
+
+impl GameCommand {
+    pub const ALL: [GameCommand; 11] = [
+        GameCommand::InitRace,
+        GameCommand::InitSeriesRace,
+        GameCommand::StartRace,
+        GameCommand::PauseRace,
+        GameCommand::ResumeRace,
+        GameCommand::ResetRace,
+        GameCommand::SetPlayerInput,
+        GameCommand::SaveReplay,
+        GameCommand::LoadReplay,
+        GameCommand::SeekReplay,
+        GameCommand::SetReplaySpeed,
+    ];
+
+    /// Tauri command name
+    pub fn name(self) -> &'static str {
+        match self {
+            GameCommand::InitRace => "init_race",
+            GameCommand::InitSeriesRace => "init_series_race",
+            GameCommand::StartRace => "start_race",
+            GameCommand::PauseRace => "pause_race",
+            GameCommand::ResumeRace => "resume_race",
+            GameCommand::ResetRace => "reset_race",
+            GameCommand::SetPlayerInput => "set_player_input",
+            GameCommand::SaveReplay => "save_replay",
+            GameCommand::LoadReplay => "load_replay",
+            GameCommand::SeekReplay => "seek_replay",
+            GameCommand::SetReplaySpeed => "set_replay_speed",
+        }
+    }
+
+    /// Whether the command can be used in `state`. The server may still
+    /// refuse it for other reasons (no player in the race, no replay...).
+    pub fn allowed_in(self, state: GameState) -> bool {
+        use GameState::*;
+
+        match self {
+            GameCommand::InitRace | GameCommand::InitSeriesRace | GameCommand::LoadReplay => state != Loading,
+            GameCommand::StartRace => state == Ready,
+            GameCommand::PauseRace => state.is_running(),
+            GameCommand::ResumeRace => state == Paused,
+            GameCommand::ResetRace | GameCommand::SaveReplay => true,
+            GameCommand::SetPlayerInput => matches!(state, Ready | Racing | Paused | Results),
+            GameCommand::SeekReplay | GameCommand::SetReplaySpeed => matches!(state, Replay | Paused),
+        }
+    }
+}
+
+/// Current game and race state, and the game states entered since they
+/// were last taken
+#[derive(Debug, Clone)]
+pub struct StateMachine {
+    state: GameState,
+    race: Option<RaceStatus>,
+    entered: Vec<GameState>,
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self {
+            state: GameState::Idle,
+            race: None,
+            entered: Vec::new(),
+        }
+    }
+
+    /// Current game state
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// Status of the active race, if there is one
+    pub fn race_status(&self) -> Option<RaceStatus> {
+        self.race
+    }
+
+    /// Move to `to`, returning the state left behind
+    pub fn transition(&mut self, to: GameState) -> Result<GameState, GameError> {
+        let from = self.state;
+        if !from.can_transition_to(to) {
+            return Err(GameError::InvalidTransition { from, to });
+        }
+
+        self.state = to;
+        self.entered.push(to);
+        if matches!(to, GameState::Idle | GameState::Loading | GameState::Replay) {
+            self.race = None;
+        }
+        Ok(from)
+    }
+
+    /// Track the status of the active race. A new race may start at any
+    /// status; after that it must follow the race transition table.
+    pub fn set_race_status(&mut self, to: RaceStatus) -> Result<(), GameError> {
+        match self.race {
+            Some(from) if from == to => Ok(()),
+            Some(from) if !from.can_transition_to(to) => Err(GameError::InvalidRaceTransition { from, to }),
+            _ => {
+                self.race = Some(to);
+                Ok(())
+            }
+        }
+    }
+
+    /// Reject `command` if it can't be used in the current state
+    pub fn check(&self, command: GameCommand) -> Result<(), GameError> {
+        if command.allowed_in(self.state) {
+            Ok(())
+        } else {
+            Err(GameError::InvalidState { command: command.name(), state: self.state })
+        }
+    }
+
+    /// Game states entered since the last call, oldest first
+    pub fn take_entered(&mut self) -> Vec<GameState> {
+        std::mem::take(&mut self.entered)
+    }
+}
+
+impl Default for StateMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GameState::*;
+
+    const STATES: [GameState; 7] = [Idle, Loading, Ready, Racing, Paused, Results, Replay];
+
+    #[test]
+    fn game_transition_table() {
+        let mut allowed = vec![
+            (Ready, Racing),
+            (Racing, Paused),
+            (Racing, Results),
+            (Paused, Racing),
+            (Paused, Replay),
+            (Idle, Replay),
+            (Replay, Paused),
+        ];
+        for from in STATES {
+            allowed.push((from, Idle));
+            if from != Loading {
+                allowed.push((from, Loading));
+            }
+        }
+        allowed.push((Loading, Ready));
+
+        for from in STATES {
+            for to in STATES {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from, to)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn race_status_only_goes_forward() {
+        use RaceStatus::*;
+        let order = [NotStarted, Countdown, Racing, Finished];
+
+        for (i, from) in order.iter().enumerate() {
+            for (j, to) in order.iter().enumerate() {
+                assert_eq!(from.can_transition_to(*to), j == i + 1, "{:?} -> {:?}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn commands_allowed_per_state() {
+        let allowed = |command: GameCommand| -> Vec<GameState> {
+            STATES.into_iter().filter(|&state| command.allowed_in(state)).collect()
+        };
+
+        assert_eq!(allowed(GameCommand::InitRace), vec![Idle, Ready, Racing, Paused, Results, Replay]);
+        assert_eq!(allowed(GameCommand::InitSeriesRace), allowed(GameCommand::InitRace));
+        assert_eq!(allowed(GameCommand::LoadReplay), allowed(GameCommand::InitRace));
+        assert_eq!(allowed(GameCommand::StartRace), vec![Ready]);
+        assert_eq!(allowed(GameCommand::PauseRace), vec![Racing, Replay]);
+        assert_eq!(allowed(GameCommand::ResumeRace), vec![Paused]);
+        assert_eq!(allowed(GameCommand::ResetRace), STATES.to_vec());
+        assert_eq!(allowed(GameCommand::SaveReplay), STATES.to_vec());
+        assert_eq!(allowed(GameCommand::SetPlayerInput), vec![Ready, Racing, Paused, Results]);
+        assert_eq!(allowed(GameCommand::SeekReplay), vec![Paused, Replay]);
+        assert_eq!(allowed(GameCommand::SetReplaySpeed), vec![Paused, Replay]);
+    }
+
+    #[test]
+    fn command_names_match_their_serialized_form() {
+        for command in GameCommand::ALL {
+            assert_eq!(serde_json::to_value(command).unwrap(), command.name());
+        }
+    }
+
+    #[test]
+    fn machine_rejects_bad_moves_and_tracks_entered_states() {
+        let mut machine = StateMachine::new();
+
+        assert_eq!(
+            machine.transition(Racing),
+            Err(GameError::InvalidTransition { from: Idle, to: Racing })
+        );
+        assert_eq!(machine.state(), Idle);
+        assert_eq!(
+            machine.check(GameCommand::StartRace),
+            Err(GameError::InvalidState { command: "start_race", state: Idle })
+        );
+
+        machine.transition(Loading).unwrap();
+        machine.transition(Ready).unwrap();
+        machine.set_race_status(RaceStatus::NotStarted).unwrap();
+        machine.transition(Racing).unwrap();
+        machine.set_race_status(RaceStatus::Countdown).unwrap();
+        assert_eq!(
+            machine.set_race_status(RaceStatus::NotStarted),
+            Err(GameError::InvalidRaceTransition { from: RaceStatus::Countdown, to: RaceStatus::NotStarted })
+        );
+        assert_eq!(machine.take_entered(), vec![Loading, Ready, Racing]);
+
+        machine.transition(Idle).unwrap();
+        assert_eq!(machine.race_status(), None);
+        assert_eq!(machine.take_entered(), vec![Idle]);
+    }
+}
@@ -0,0 +1,102 @@
+//! Track - 400m oval geometry
+//!
+//! Port of the track math in Track.js. Maps distance along the track and
+//! lane position to world coordinates, so every client places runners
+//! identically.
+
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Track geometry constants
+pub const STRAIGHT_LENGTH: f32 = 84.39;
+pub const INNER_RADIUS: f32 = 36.5;
+pub const LANE_WIDTH: f32 = 1.22;
+pub const NUM_LANES: u32 = 8;
+/// Nominal lap length, measured in lane 1
+pub const LAP_LENGTH: f32 = 400.0;
+
+/// Part of the oval a position falls on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrackSection {
+    Straight,
+    Bend,
+}
+
+/// World placement on the track
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TrackPosition {
+    pub x: f32,
+    pub z: f32,
+    /// Heading around the Y axis (radians)
+    pub rotation: f32,
+    pub section: TrackSection,
+}
+
+/// Running radius of the bends for a lane position
+pub fn lane_radius(lane: f32) -> f32 {
+    INNER_RADIUS + (lane - 0.5) * LANE_WIDTH
+}
+
+/// Length of one lap for a lane position
+pub fn track_length(lane: f32) -> f32 {
+    (STRAIGHT_LENGTH * 2.0) + (2.0 * PI * lane_radius(lane))
+}
+
+/// How far ahead of lane 1 a lane starts so that running `bends` bends
+/// in that lane covers the same distance (2 bends = one full lap)
+pub fn stagger_distance(lane: u32, bends: u32) -> f32 {
+    bends as f32 * PI * (lane_radius(lane as f32) - lane_radius(1.0))
+}
+
+/// Get position and heading on the track given distance traveled and lane
+pub fn track_position(distance: f32, lane: f32) -> TrackPosition {
+    let radius = lane_radius(lane);
+
+    // Normalize distance to track length (loop). Negative distances stay
+    // negative and extend the first straight behind the start line.
+    let distance = distance % track_length(lane);
+
+    let half_straight = STRAIGHT_LENGTH / 2.0;
+    let curve_length = PI * radius;
+
+    let seg1_end = STRAIGHT_LENGTH;
+    let seg2_end = seg1_end + curve_length;
+    let seg3_end = seg2_end + STRAIGHT_LENGTH;
+
+    if distance < seg1_end {
+        // Bottom straight - running from right to left
+        TrackPosition {
+            x: half_straight - distance,
+            z: -radius,
+            rotation: PI,
+            section: TrackSection::Straight,
+        }
+    } else if distance < seg2_end {
+        // Left curve
+        let angle = -FRAC_PI_2 - (distance - seg1_end) / radius;
+        TrackPosition {
+            x: -half_straight + angle.cos() * radius,
+            z: angle.sin() * radius,
+            rotation: angle - FRAC_PI_2,
+            section: TrackSection::Bend,
+        }
+    } else if distance < seg3_end {
+        // Top straight - running from left to right
+        TrackPosition {
+            x: -half_straight + (distance - seg2_end),
+            z: radius,
+            rotation: 0.0,
+            section: TrackSection::Straight,
+        }
+    } else {
+        // Right curve
+        let angle = FRAC_PI_2 - (distance - seg3_end) / radius;
+        TrackPosition {
+            x: half_straight + angle.cos() * radius,
+            z: angle.sin() * radius,
+            rotation: angle - FRAC_PI_2,
+            section: TrackSection::Bend,
+        }
+    }
+}
+
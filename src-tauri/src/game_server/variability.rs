@@ -0,0 +1,125 @@
+//! Variability - Surges, bad patches and recoveries
+//!
+//! Gives AI runners a random in-race form on top of their planned pace,
+//! so places change during the race instead of being fixed by the
+//! generated finish times. Events are rolled per meter covered and draw
+//! from the race RNG, so they are independent of tick rate and time scale
+//! and equal seeds still produce identical races.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// What a runner's form is doing right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FormPhase {
+    /// Running to plan
+    #[default]
+    Steady,
+    /// Temporarily pushing above planned pace
+    Surge,
+    /// Temporarily dropping off planned pace
+    BadPatch,
+    /// Bouncing back after a bad patch
+    Recovery,
+}
+
+/// A runner's in-race form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Form {
+    pub phase: FormPhase,
+    /// Multiplier on planned speed
+    pub multiplier: f32,
+    /// Meters left in the current phase
+    pub remaining: f32,
+}
+
+impl Default for Form {
+    fn default() -> Self {
+        Self {
+            phase: FormPhase::Steady,
+            multiplier: 1.0,
+            remaining: 0.0,
+        }
+    }
+}
+
+/// In-race variability model
+pub struct Variability;
+
+impl Variability {
+    /// Expected events per meter at intensity 1.0
+    const SURGE_RATE: f32 = 1.0 / 1500.0;
+    const BAD_PATCH_RATE: f32 = 1.0 / 1800.0;
+    /// Chance a bad patch is followed by a recovery
+    const RECOVERY_CHANCE: f32 = 0.6;
+    /// Phase lengths (meters)
+    const SURGE_LENGTH: (f32, f32) = (80.0, 200.0);
+    const BAD_PATCH_LENGTH: (f32, f32) = (150.0, 400.0);
+    const RECOVERY_LENGTH: (f32, f32) = (100.0, 300.0);
+    /// Speed change at intensity 1.0 (fraction of planned pace)
+    const SURGE_BOOST: (f32, f32) = (0.03, 0.06);
+    const BAD_PATCH_DROP: (f32, f32) = (0.04, 0.10);
+    const RECOVERY_BOOST: (f32, f32) = (0.01, 0.03);
+    /// Limits on the form multiplier however high the intensity
+    const MIN_MULTIPLIER: f32 = 0.7;
+    const MAX_MULTIPLIER: f32 = 1.15;
+
+    /// Advance a runner's form after it moved `moved` meters. `intensity`
+    /// scales how often events happen and how big they are (0 = off).
+    pub fn update<R: Rng + ?Sized>(form: &mut Form, moved: f32, intensity: f32, rng: &mut R) {
+        if intensity <= 0.0 {
+            *form = Form::default();
+            return;
+        }
+
+        if form.phase != FormPhase::Steady {
+            form.remaining -= moved;
+            if form.remaining > 0.0 {
+                return;
+            }
+
+            // A bad patch may be followed by a recovery, everything else settles
+            if form.phase == FormPhase::BadPatch && rng.gen::<f32>() < Self::RECOVERY_CHANCE {
+                Self::begin(form, FormPhase::Recovery, intensity, rng);
+            } else {
+                *form = Form::default();
+            }
+            return;
+        }
+
+        let roll = rng.gen::<f32>();
+        let surge_chance = Self::SURGE_RATE * moved * intensity;
+        let bad_patch_chance = Self::BAD_PATCH_RATE * moved * intensity;
+
+        if roll < surge_chance {
+            Self::begin(form, FormPhase::Surge, intensity, rng);
+        } else if roll < surge_chance + bad_patch_chance {
+            Self::begin(form, FormPhase::BadPatch, intensity, rng);
+        }
+    }
+
+    fn begin<R: Rng + ?Sized>(form: &mut Form, phase: FormPhase, intensity: f32, rng: &mut R) {
+        let (length, change) = match phase {
+            FormPhase::Steady => {
+                *form = Form::default();
+                return;
+            }
+            FormPhase::Surge => (Self::SURGE_LENGTH, Self::SURGE_BOOST),
+            FormPhase::BadPatch => (Self::BAD_PATCH_LENGTH, Self::BAD_PATCH_DROP),
+            FormPhase::Recovery => (Self::RECOVERY_LENGTH, Self::RECOVERY_BOOST),
+        };
+
+        let change = rng.gen_range(change.0..change.1) * intensity;
+        let multiplier = if phase == FormPhase::BadPatch {
+            1.0 - change
+        } else {
+            1.0 + change
+        };
+
+        *form = Form {
+            phase,
+            multiplier: multiplier.clamp(Self::MIN_MULTIPLIER, Self::MAX_MULTIPLIER),
+            remaining: rng.gen_range(length.0..length.1),
+        };
+    }
+}
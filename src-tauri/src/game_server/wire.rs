@@ -0,0 +1,595 @@
+//! Wire - Compact binary snapshot encoding
+//!
+//! Encodes `TickResult`s as versioned binary frames for IPC. Runner floats
+//! are quantized to integers, and frames after the first are deltas
+//! against the last snapshot the client acknowledged, so only the fields
+//! that changed are sent. The byte layout is specified in
+//! `SNAPSHOT_FORMAT.md`; `SnapshotDecoder` is the reference decoder.
+
+use std::collections::VecDeque;
+use crate::game_server::race::{RaceSnapshot, RaceStatus};
+use crate::game_server::runner::RunnerSnapshot;
+use crate::game_server::simulation::TickResult;
+use crate::game_server::track::{TrackPosition, TrackSection};
+
+/// Wire format version
+pub const WIRE_VERSION: u8 = 2;
+
+/// Frame kinds
+const FRAME_KEY: u8 = 0;
+const FRAME_DELTA: u8 = 1;
+
+/// Snapshots kept for use as delta bases
+const HISTORY_LEN: usize = 120;
+
+/// Per-runner quantized fields, in wire order (bit N of the field mask)
+const FIELD_DISTANCE: usize = 0;
+const FIELD_LANE_POSITION: usize = 1;
+const FIELD_SPEED: usize = 2;
+const FIELD_ANIMATION_PHASE: usize = 3;
+const FIELD_ENERGY: usize = 4;
+const FIELD_FLAGS: usize = 5;
+const FIELD_LANE: usize = 6;
+const FIELD_FINISH_DISTANCE: usize = 7;
+const FIELD_WORLD_X: usize = 8;
+const FIELD_WORLD_Z: usize = 9;
+const FIELD_WORLD_ROTATION: usize = 10;
+const FIELD_PLACE: usize = 11;
+const FIELD_GAP: usize = 12;
+const FIELD_GAP_TIME: usize = 13;
+const FIELD_INTERVAL: usize = 14;
+const FIELD_INTERVAL_TIME: usize = 15;
+const FIELD_COUNT: usize = 16;
+
+/// Quantization steps per unit
+const DISTANCE_SCALE: f32 = 100.0; // cm
+const LANE_SCALE: f32 = 1000.0; // 1/1000 lane
+const SPEED_SCALE: f32 = 100.0; // cm/s
+const PHASE_SCALE: f32 = 1000.0; // 1/1000 cycle
+const ENERGY_SCALE: f32 = 10.0; // 0.1 %
+const WORLD_SCALE: f32 = 100.0; // cm
+const ROTATION_SCALE: f32 = 1000.0; // mrad
+const TIME_SCALE: f32 = 100.0; // 1/100 s
+
+/// Runner flag bits (`FIELD_FLAGS`)
+const FLAG_FINISHED: i64 = 1 << 0;
+const FLAG_DRAFTING: i64 = 1 << 1;
+const FLAG_KICKING: i64 = 1 << 2;
+const FLAG_PLAYER: i64 = 1 << 3;
+const FLAG_WORLD: i64 = 1 << 4;
+const FLAG_BEND: i64 = 1 << 5;
+
+/// A runner snapshot with every field quantized to an integer
+#[derive(Debug, Clone, PartialEq)]
+struct QuantizedRunner {
+    id: u32,
+    fields: [i64; FIELD_COUNT],
+}
+
+impl QuantizedRunner {
+    fn quantize(value: f32, scale: f32) -> i64 {
+        (value * scale).round() as i64
+    }
+
+    fn from_snapshot(runner: &RunnerSnapshot) -> Self {
+        let mut fields = [0; FIELD_COUNT];
+        fields[FIELD_DISTANCE] = Self::quantize(runner.distance, DISTANCE_SCALE);
+        fields[FIELD_LANE_POSITION] = Self::quantize(runner.lane_position, LANE_SCALE);
+        fields[FIELD_SPEED] = Self::quantize(runner.speed, SPEED_SCALE);
+        fields[FIELD_ANIMATION_PHASE] = Self::quantize(runner.animation_phase, PHASE_SCALE);
+        fields[FIELD_ENERGY] = Self::quantize(runner.energy, ENERGY_SCALE);
+        fields[FIELD_LANE] = runner.lane as i64;
+        fields[FIELD_FINISH_DISTANCE] = Self::quantize(runner.finish_distance, DISTANCE_SCALE);
+        fields[FIELD_PLACE] = runner.place as i64;
+        fields[FIELD_GAP] = Self::quantize(runner.gap, DISTANCE_SCALE);
+        fields[FIELD_GAP_TIME] = Self::quantize(runner.gap_time, TIME_SCALE);
+        fields[FIELD_INTERVAL] = Self::quantize(runner.interval, DISTANCE_SCALE);
+        fields[FIELD_INTERVAL_TIME] = Self::quantize(runner.interval_time, TIME_SCALE);
+
+        let mut flags = 0;
+        for (set, bit) in [
+            (runner.finished, FLAG_FINISHED),
+            (runner.drafting, FLAG_DRAFTING),
+            (runner.kicking, FLAG_KICKING),
+            (runner.player, FLAG_PLAYER),
+        ] {
+            if set {
+                flags |= bit;
+            }
+        }
+
+        if let Some(world) = &runner.world {
+            flags |= FLAG_WORLD;
+            if world.section == TrackSection::Bend {
+                flags |= FLAG_BEND;
+            }
+            fields[FIELD_WORLD_X] = Self::quantize(world.x, WORLD_SCALE);
+            fields[FIELD_WORLD_Z] = Self::quantize(world.z, WORLD_SCALE);
+            fields[FIELD_WORLD_ROTATION] = Self::quantize(world.rotation, ROTATION_SCALE);
+        }
+        fields[FIELD_FLAGS] = flags;
+
+        Self { id: runner.id, fields }
+    }
+
+    fn to_snapshot(&self) -> RunnerSnapshot {
+        let value = |field: usize, scale: f32| self.fields[field] as f32 / scale;
+        let flags = self.fields[FIELD_FLAGS];
+
+        let world = (flags & FLAG_WORLD != 0).then(|| TrackPosition {
+            x: value(FIELD_WORLD_X, WORLD_SCALE),
+            z: value(FIELD_WORLD_Z, WORLD_SCALE),
+            rotation: value(FIELD_WORLD_ROTATION, ROTATION_SCALE),
+            section: if flags & FLAG_BEND != 0 {
+                TrackSection::Bend
+            } else {
+                TrackSection::Straight
+            },
+        });
+
+        RunnerSnapshot {
+            id: self.id,
+            distance: value(FIELD_DISTANCE, DISTANCE_SCALE),
+            lane_position: value(FIELD_LANE_POSITION, LANE_SCALE),
+            speed: value(FIELD_SPEED, SPEED_SCALE),
+            animation_phase: value(FIELD_ANIMATION_PHASE, PHASE_SCALE),
+            finished: flags & FLAG_FINISHED != 0,
+            drafting: flags & FLAG_DRAFTING != 0,
+            energy: value(FIELD_ENERGY, ENERGY_SCALE),
+            kicking: flags & FLAG_KICKING != 0,
+            player: flags & FLAG_PLAYER != 0,
+            lane: self.fields[FIELD_LANE] as u32,
+            finish_distance: value(FIELD_FINISH_DISTANCE, DISTANCE_SCALE),
+            place: self.fields[FIELD_PLACE] as u32,
+            gap: value(FIELD_GAP, DISTANCE_SCALE),
+            gap_time: value(FIELD_GAP_TIME, TIME_SCALE),
+            interval: value(FIELD_INTERVAL, DISTANCE_SCALE),
+            interval_time: value(FIELD_INTERVAL_TIME, TIME_SCALE),
+            world,
+        }
+    }
+
+    /// Fields that differ from `base`, as a bit mask
+    fn changed_mask(&self, base: &[i64; FIELD_COUNT]) -> u64 {
+        (0..FIELD_COUNT)
+            .filter(|&i| self.fields[i] != base[i])
+            .fold(0, |mask, i| mask | (1 << i))
+    }
+}
+
+/// Encodes tick results into binary frames, delta-compressing against the
+/// last acknowledged snapshot
+#[derive(Debug, Default)]
+pub struct SnapshotEncoder {
+    /// Sequence number of the last encoded frame
+    sequence: u32,
+    /// Latest sequence the client has decoded
+    acked: Option<u32>,
+    /// Recently encoded snapshots that may become delta bases
+    history: VecDeque<(u32, Vec<QuantizedRunner>)>,
+}
+
+impl SnapshotEncoder {
+    /// Create an encoder whose first frame is a keyframe
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that the client decoded frame `sequence`. Later frames are
+    /// encoded as deltas against it. A sequence that isn't in the recent
+    /// history (never sent, or too old) clears the ack, so the next frame
+    /// is a keyframe.
+    pub fn acknowledge(&mut self, sequence: u32) {
+        let known = self.history.iter().any(|(seq, _)| *seq == sequence);
+        self.acked = known.then_some(sequence);
+    }
+
+    /// Forget all acknowledgements so the next frame is a keyframe
+    pub fn reset(&mut self) {
+        self.acked = None;
+        self.history.clear();
+    }
+
+    /// Encode a tick result as the next frame
+    pub fn encode(&mut self, tick: &TickResult) -> Vec<u8> {
+        self.sequence = self.sequence.wrapping_add(1);
+
+        let runners: Vec<QuantizedRunner> = tick.snapshot.runners.iter().map(QuantizedRunner::from_snapshot).collect();
+
+        // Deltas need the acked base to hold the same runners in the same order
+        let base = self.acked.and_then(|acked| {
+            self.history
+                .iter()
+                .find(|(seq, base)| {
+                    *seq == acked
+                        && base.len() == runners.len()
+                        && base.iter().zip(&runners).all(|(a, b)| a.id == b.id)
+                })
+                .map(|(seq, base)| (*seq, base))
+        });
+
+        let mut out = Vec::with_capacity(32 + runners.len() * 8);
+        out.push(WIRE_VERSION);
+
+        match base {
+            Some((base_sequence, _)) => {
+                out.push(FRAME_DELTA);
+                write_varint(&mut out, self.sequence as u64);
+                write_varint(&mut out, base_sequence as u64);
+            }
+            None => {
+                out.push(FRAME_KEY);
+                write_varint(&mut out, self.sequence as u64);
+            }
+        }
+
+        let snapshot = &tick.snapshot;
+        out.push(status_to_byte(snapshot.status));
+        out.extend_from_slice(&snapshot.elapsed_time.to_le_bytes());
+        out.extend_from_slice(&snapshot.countdown.to_le_bytes());
+        out.extend_from_slice(&tick.alpha.to_le_bytes());
+        write_varint(&mut out, tick.steps as u64);
+        write_varint(&mut out, snapshot.finisher_count as u64);
+        write_varint(&mut out, runners.len() as u64);
+
+        match base {
+            Some((_, base)) => {
+                let changed: Vec<(usize, u64)> = runners
+                    .iter()
+                    .zip(base)
+                    .map(|(runner, base)| runner.changed_mask(&base.fields))
+                    .enumerate()
+                    .filter(|(_, mask)| *mask != 0)
+                    .collect();
+
+                write_varint(&mut out, changed.len() as u64);
+                for (index, mask) in changed {
+                    write_varint(&mut out, index as u64);
+                    write_fields(&mut out, mask, &runners[index].fields, &base[index].fields);
+                }
+            }
+            None => {
+                for runner in &runners {
+                    write_varint(&mut out, runner.id as u64);
+                    let mask = runner.changed_mask(&[0; FIELD_COUNT]);
+                    write_fields(&mut out, mask, &runner.fields, &[0; FIELD_COUNT]);
+                }
+            }
+        }
+
+        self.history.push_back((self.sequence, runners));
+        if self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+
+        out
+    }
+}
+
+/// A decoded frame
+#[derive(Debug, Clone)]
+pub struct DecodedFrame {
+    /// Sequence number to acknowledge back to the encoder
+    pub sequence: u32,
+    pub tick: TickResult,
+}
+
+/// Reference decoder for the binary snapshot format
+#[derive(Debug, Default)]
+pub struct SnapshotDecoder {
+    /// Recently decoded snapshots that may be delta bases
+    history: VecDeque<(u32, Vec<QuantizedRunner>)>,
+}
+
+impl SnapshotDecoder {
+    /// Create a decoder with no delta bases
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a frame produced by `SnapshotEncoder`
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<DecodedFrame, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let version = reader.u8()?;
+        if version != WIRE_VERSION {
+            return Err(format!("Unsupported snapshot version {}", version));
+        }
+
+        let kind = reader.u8()?;
+        let sequence = reader.varint()? as u32;
+        let base_sequence = match kind {
+            FRAME_KEY => None,
+            FRAME_DELTA => Some(reader.varint()? as u32),
+            other => return Err(format!("Unknown frame kind {}", other)),
+        };
+
+        let status = status_from_byte(reader.u8()?)?;
+        let elapsed_time = reader.f32()?;
+        let countdown = reader.f32()?;
+        let alpha = reader.f32()?;
+        let steps = reader.varint()? as u32;
+        let finisher_count = reader.varint()? as u32;
+        let runner_count = reader.varint()? as usize;
+
+        let runners = match base_sequence {
+            Some(base_sequence) => {
+                let mut runners = self
+                    .history
+                    .iter()
+                    .find(|(seq, _)| *seq == base_sequence)
+                    .map(|(_, base)| base.clone())
+                    .ok_or_else(|| format!("Missing delta base {}", base_sequence))?;
+                if runners.len() != runner_count {
+                    return Err("Delta base runner count mismatch".to_string());
+                }
+
+                let record_count = reader.varint()?;
+                for _ in 0..record_count {
+                    let index = reader.varint()? as usize;
+                    let runner = runners.get_mut(index).ok_or("Runner index out of range")?;
+                    let base = runner.fields;
+                    read_fields(&mut reader, &mut runner.fields, &base)?;
+                }
+                runners
+            }
+            None => {
+                let mut runners = Vec::with_capacity(runner_count);
+                for _ in 0..runner_count {
+                    let id = reader.varint()? as u32;
+                    let mut fields = [0; FIELD_COUNT];
+                    read_fields(&mut reader, &mut fields, &[0; FIELD_COUNT])?;
+                    runners.push(QuantizedRunner { id, fields });
+                }
+                runners
+            }
+        };
+
+        if reader.pos != bytes.len() {
+            return Err("Trailing bytes after snapshot".to_string());
+        }
+
+        let snapshot = RaceSnapshot {
+            status,
+            elapsed_time,
+            countdown,
+            runners: runners.iter().map(QuantizedRunner::to_snapshot).collect(),
+            finisher_count,
+        };
+
+        self.history.push_back((sequence, runners));
+        if self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+
+        Ok(DecodedFrame {
+            sequence,
+            tick: TickResult {
+                snapshot,
+                alpha,
+                steps,
+                events: Vec::new(),
+                place_changes: Vec::new(),
+            },
+        })
+    }
+}
+
+fn status_to_byte(status: RaceStatus) -> u8 {
+    match status {
+        RaceStatus::NotStarted => 0,
+        RaceStatus::Countdown => 1,
+        RaceStatus::Racing => 2,
+        RaceStatus::Finished => 3,
+    }
+}
+
+fn status_from_byte(byte: u8) -> Result<RaceStatus, String> {
+    match byte {
+        0 => Ok(RaceStatus::NotStarted),
+        1 => Ok(RaceStatus::Countdown),
+        2 => Ok(RaceStatus::Racing),
+        3 => Ok(RaceStatus::Finished),
+        other => Err(format!("Unknown race status {}", other)),
+    }
+}
+
+/// Write the field mask, then each masked field as a zigzag delta from `base`
+fn write_fields(out: &mut Vec<u8>, mask: u64, fields: &[i64; FIELD_COUNT], base: &[i64; FIELD_COUNT]) {
+    write_varint(out, mask);
+    for i in (0..FIELD_COUNT).filter(|i| mask & (1 << i) != 0) {
+        write_varint(out, zigzag(fields[i] - base[i]));
+    }
+}
+
+fn read_fields(reader: &mut Reader, fields: &mut [i64; FIELD_COUNT], base: &[i64; FIELD_COUNT]) -> Result<(), String> {
+    let mask = reader.varint()?;
+    if mask >> FIELD_COUNT != 0 {
+        return Err("Unknown runner field in mask".to_string());
+    }
+    for i in (0..FIELD_COUNT).filter(|i| mask & (1 << i) != 0) {
+        fields[i] = base[i] + unzigzag(reader.varint()?);
+    }
+    Ok(())
+}
+
+/// Unsigned LEB128
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Cursor over an encoded frame
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.pos).ok_or("Truncated snapshot")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .ok_or("Truncated snapshot")?;
+        self.pos += 4;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Varint too long".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runner(id: u32, distance: f32) -> RunnerSnapshot {
+        RunnerSnapshot {
+            id,
+            distance,
+            lane_position: 1.25,
+            speed: 5.5,
+            animation_phase: 0.5,
+            finished: false,
+            drafting: id == 1,
+            energy: 87.5,
+            kicking: false,
+            player: id == 0,
+            lane: 1,
+            finish_distance: 400.0,
+            place: id + 1,
+            gap: distance,
+            gap_time: 1.5,
+            interval: 0.75,
+            interval_time: 0.25,
+            world: Some(TrackPosition { x: 12.5, z: -3.25, rotation: 1.5, section: TrackSection::Bend }),
+        }
+    }
+
+    fn tick(runners: Vec<RunnerSnapshot>) -> TickResult {
+        TickResult {
+            snapshot: RaceSnapshot {
+                status: RaceStatus::Racing,
+                elapsed_time: 12.5,
+                countdown: 0.0,
+                runners,
+                finisher_count: 0,
+            },
+            alpha: 0.25,
+            steps: 1,
+            events: Vec::new(),
+            place_changes: Vec::new(),
+        }
+    }
+
+    fn quantized(tick: &TickResult) -> Vec<QuantizedRunner> {
+        tick.snapshot.runners.iter().map(QuantizedRunner::from_snapshot).collect()
+    }
+
+    #[test]
+    fn keyframe_round_trips() {
+        let sent = tick(vec![runner(0, 10.0), runner(1, 9.5)]);
+        let bytes = SnapshotEncoder::new().encode(&sent);
+        assert_eq!(bytes[1], FRAME_KEY);
+
+        let frame = SnapshotDecoder::new().decode(&bytes).unwrap();
+        assert_eq!(frame.sequence, 1);
+        assert_eq!(frame.tick.snapshot.status, RaceStatus::Racing);
+        assert_eq!(frame.tick.snapshot.elapsed_time, 12.5);
+        assert_eq!(frame.tick.alpha, 0.25);
+        assert_eq!(quantized(&frame.tick), quantized(&sent));
+    }
+
+    #[test]
+    fn delta_against_acked_base() {
+        let mut encoder = SnapshotEncoder::new();
+        let mut decoder = SnapshotDecoder::new();
+
+        let first = decoder.decode(&encoder.encode(&tick(vec![runner(0, 10.0), runner(1, 9.5)]))).unwrap();
+        encoder.acknowledge(first.sequence);
+
+        let sent = tick(vec![runner(0, 10.0), runner(1, 11.0)]);
+        let bytes = encoder.encode(&sent);
+        assert_eq!(bytes[1], FRAME_DELTA);
+
+        let frame = decoder.decode(&bytes).unwrap();
+        assert_eq!(frame.sequence, 2);
+        assert_eq!(quantized(&frame.tick), quantized(&sent));
+    }
+
+    #[test]
+    fn unknown_ack_sends_keyframe() {
+        let mut encoder = SnapshotEncoder::new();
+        encoder.encode(&tick(vec![runner(0, 10.0)]));
+        encoder.acknowledge(42);
+
+        let sent = tick(vec![runner(0, 11.0)]);
+        let bytes = encoder.encode(&sent);
+        assert_eq!(bytes[1], FRAME_KEY);
+
+        // A fresh decoder has no bases, so this only works for a keyframe
+        let frame = SnapshotDecoder::new().decode(&bytes).unwrap();
+        assert_eq!(quantized(&frame.tick), quantized(&sent));
+    }
+
+    #[test]
+    fn ack_ahead_of_sent_sends_keyframe() {
+        let mut encoder = SnapshotEncoder::new();
+        let mut decoder = SnapshotDecoder::new();
+
+        let first = decoder.decode(&encoder.encode(&tick(vec![runner(0, 10.0)]))).unwrap();
+        encoder.acknowledge(first.sequence);
+        encoder.acknowledge(first.sequence + 5);
+        assert_eq!(encoder.encode(&tick(vec![runner(0, 11.0)]))[1], FRAME_KEY);
+
+        // Resending the last good ack recovers deltas
+        encoder.acknowledge(first.sequence);
+        let sent = tick(vec![runner(0, 12.0)]);
+        let bytes = encoder.encode(&sent);
+        assert_eq!(bytes[1], FRAME_DELTA);
+        assert_eq!(quantized(&decoder.decode(&bytes).unwrap().tick), quantized(&sent));
+    }
+
+    #[test]
+    fn rejects_unknown_field_bits() {
+        let mut bytes = vec![WIRE_VERSION, FRAME_KEY];
+        write_varint(&mut bytes, 1); // sequence
+        bytes.push(status_to_byte(RaceStatus::Racing));
+        for value in [0.0f32, 0.0, 0.0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        write_varint(&mut bytes, 0); // steps
+        write_varint(&mut bytes, 0); // finishers
+        write_varint(&mut bytes, 1); // runners
+        write_varint(&mut bytes, 0); // runner id
+        write_varint(&mut bytes, 1 << FIELD_COUNT);
+
+        assert!(SnapshotDecoder::new().decode(&bytes).is_err());
+    }
+}
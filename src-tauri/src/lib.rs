@@ -2,109 +2,502 @@
 //!
 //! Provides the game server for local AI mode and commands for frontend communication.
 
-mod game_server;
+pub mod game_server;
 
-use game_server::race::{RaceConfig, RaceSnapshot, RaceResult};
-use game_server::simulation::{GameServer, GameState, ServerStats};
-use std::sync::Mutex;
-use tauri::State;
+use game_server::commentary::{CommentaryLine, Phrasebook};
+use game_server::error::GameError;
+use game_server::net::NetServer;
+use game_server::pacing::PacingMix;
+use game_server::race::{Entrant, PlayerConfig, RaceConfig, RaceSnapshot, RaceResult, RunnerSplits, StartFormation, TimingPoints};
+use game_server::replay::{Replay, ReplayInfo};
+use game_server::results::{AthleteId, HistoryEntry, Mark, RaceSummary, ResultsDb};
+use game_server::roster::{Athlete, Roster};
+use game_server::runner::PlayerInput;
+use game_server::runtime::{SimCommand, SimEvent, SimulationThread};
+use game_server::series::{Series, SeriesEvent, SeriesInfo, Standing, TieBreak};
+use game_server::simulation::{GameServer, GameState, ServerStats, TickResult};
+use game_server::state::GameCommand;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::ipc::{Channel, InvokeResponseBody};
+use tauri::path::BaseDirectory;
+use tauri::{Emitter, Manager, State, Window};
+
+/// Event carrying the race events and place changes of a tick, when there are any
+const EVENTS_EVENT: &str = "race-events";
+/// Event carrying each `GameState` the server enters, in order
+const STATUS_EVENT: &str = "race-status";
+/// Event carrying new commentary lines as they are said
+const COMMENTARY_EVENT: &str = "race-commentary";
+/// Series save file, in the app data dir
+const SERIES_FILE: &str = "series.json";
+/// Results database, in the app data dir
+const RESULTS_FILE: &str = "results.db";
+/// Roster loaded at startup, bundled as a resource
+const DEFAULT_ROSTER: &str = "rosters/default.json";
 
 /// Initialize a new race with the given configuration
 #[tauri::command]
 fn init_race(
-    server: State<'_, Mutex<GameServer>>,
+    sim: State<'_, Arc<SimulationThread>>,
     runner_count: Option<u32>,
     time_scale: Option<f32>,
-) -> Result<(), String> {
-    let mut server = server.lock().map_err(|e| e.to_string())?;
-
-    let config = RaceConfig {
-        runner_count: runner_count.unwrap_or(100),
-        time_scale: time_scale.unwrap_or(10.0),
-        ..Default::default()
+    seed: Option<u64>,
+    distance: Option<f32>,
+    world_positions: Option<bool>,
+    start_formation: Option<StartFormation>,
+    player_name: Option<String>,
+    timing_points: Option<TimingPoints>,
+    pacing_mix: Option<PacingMix>,
+    variability: Option<f32>,
+    use_roster: Option<bool>,
+    player_target_time: Option<f32>,
+) -> Result<(), GameError> {
+    let defaults = RaceConfig::default();
+    let mut config = RaceConfig {
+        runner_count: runner_count.unwrap_or(defaults.runner_count),
+        time_scale: time_scale.unwrap_or(defaults.time_scale),
+        distance: distance.unwrap_or(defaults.distance),
+        world_positions: world_positions.unwrap_or(defaults.world_positions),
+        start_formation: start_formation.unwrap_or(defaults.start_formation),
+        player: player_name.map(|name| PlayerConfig { name, target_time: player_target_time }),
+        timing_points,
+        pacing_mix,
+        variability,
+        seed,
+        ..defaults
     };
 
     let runner_count = config.runner_count;
-    server.init_race(config);
-    log::info!("Race initialized with {} runners", runner_count);
+    let seed = sim.call(move |server| {
+        if use_roster.unwrap_or(false) {
+            server.apply_roster(&mut config)?;
+        }
+        server.init_race(config)?;
+        Ok::<_, GameError>(server.get_seed())
+    })??;
+    log::info!(
+        "Race initialized with {} runners (seed {})",
+        runner_count,
+        seed.unwrap_or_default()
+    );
     Ok(())
 }
 
 /// Start the race countdown
 #[tauri::command]
-fn start_race(server: State<'_, Mutex<GameServer>>) -> Result<(), String> {
-    let mut server = server.lock().map_err(|e| e.to_string())?;
-    server.start_race();
+fn start_race(sim: State<'_, Arc<SimulationThread>>) -> Result<(), GameError> {
+    sim.control(SimCommand::Start)?;
     log::info!("Race started");
     Ok(())
 }
 
-/// Perform a simulation tick and return the current state
+/// Set the human player's desired speed and lane change
+#[tauri::command]
+fn set_player_input(
+    sim: State<'_, Arc<SimulationThread>>,
+    desired_speed: f32,
+    lane_change: f32,
+) -> Result<(), GameError> {
+    let input = PlayerInput { desired_speed, lane_change };
+    sim.call(move |server| server.set_player_input(input))?
+}
+
+/// Get the current state with interpolation alpha. Only reads: the simulation
+/// thread does the ticking, and events only go out with the race-events event.
+#[tauri::command]
+fn tick(sim: State<'_, Arc<SimulationThread>>) -> Result<Option<TickResult>, GameError> {
+    sim.call(|server| server.current_tick())
+}
+
+/// Frame subscription of each window, by window label
+type SnapshotFeeds = Mutex<HashMap<String, u32>>;
+
+/// Push the race state to this window as binary frames (see SNAPSHOT_FORMAT.md)
+/// after every tick, replacing any feed the window already had
+#[tauri::command]
+fn subscribe_snapshots(
+    window: Window,
+    sim: State<'_, Arc<SimulationThread>>,
+    feeds: State<'_, SnapshotFeeds>,
+    frames: Channel<InvokeResponseBody>,
+) -> Result<(), GameError> {
+    let subscription = sim.subscribe_frames(move |frame| frames.send(InvokeResponseBody::Raw(frame)).is_ok())?;
+    let previous = feeds
+        .lock()
+        .map_err(|_| GameError::Unavailable)?
+        .insert(window.label().to_string(), subscription);
+    if let Some(previous) = previous {
+        sim.unsubscribe_frames(previous)?;
+    }
+    Ok(())
+}
+
+/// Acknowledge the last frame this window decoded, so the next is a delta
+/// against it. Without a sequence the next frame is a keyframe.
 #[tauri::command]
-fn tick(server: State<'_, Mutex<GameServer>>) -> Result<Option<RaceSnapshot>, String> {
-    let mut server = server.lock().map_err(|e| e.to_string())?;
-    Ok(server.tick())
+fn ack_snapshot(
+    window: Window,
+    sim: State<'_, Arc<SimulationThread>>,
+    feeds: State<'_, SnapshotFeeds>,
+    sequence: Option<u32>,
+) -> Result<(), GameError> {
+    let feeds = feeds.lock().map_err(|_| GameError::Unavailable)?;
+    match feeds.get(window.label()) {
+        Some(&subscription) => sim.ack_frame(subscription, sequence),
+        None => Ok(()),
+    }
+}
+
+/// Stop pushing frames to this window
+#[tauri::command]
+fn unsubscribe_snapshots(
+    window: Window,
+    sim: State<'_, Arc<SimulationThread>>,
+    feeds: State<'_, SnapshotFeeds>,
+) -> Result<(), GameError> {
+    let subscription = feeds.lock().map_err(|_| GameError::Unavailable)?.remove(window.label());
+    match subscription {
+        Some(subscription) => sim.unsubscribe_frames(subscription),
+        None => Ok(()),
+    }
 }
 
 /// Get current race snapshot without advancing simulation
 #[tauri::command]
-fn get_snapshot(server: State<'_, Mutex<GameServer>>) -> Result<Option<RaceSnapshot>, String> {
-    let server = server.lock().map_err(|e| e.to_string())?;
-    Ok(server.get_snapshot())
+fn get_snapshot(sim: State<'_, Arc<SimulationThread>>) -> Result<Option<RaceSnapshot>, GameError> {
+    sim.call(|server| server.get_snapshot())
 }
 
 /// Get race results
 #[tauri::command]
-fn get_results(server: State<'_, Mutex<GameServer>>) -> Result<Option<Vec<RaceResult>>, String> {
-    let server = server.lock().map_err(|e| e.to_string())?;
-    Ok(server.get_results())
+fn get_results(sim: State<'_, Arc<SimulationThread>>) -> Result<Option<Vec<RaceResult>>, GameError> {
+    sim.call(|server| server.get_results())
+}
+
+/// Get actual split times recorded so far (one runner, or every runner)
+#[tauri::command]
+fn get_splits(sim: State<'_, Arc<SimulationThread>>, runner_id: Option<u32>) -> Result<Vec<RunnerSplits>, GameError> {
+    sim.call(move |server| server.get_splits(runner_id))
+}
+
+/// Get the commentary of the current race, optionally only lines after race time `since`
+#[tauri::command]
+fn get_commentary(sim: State<'_, Arc<SimulationThread>>, since: Option<f32>) -> Result<Vec<CommentaryLine>, GameError> {
+    sim.call(move |server| server.get_commentary(since))
+}
+
+/// Load a commentary phrasebook file, returning its language
+#[tauri::command]
+fn load_phrasebook(sim: State<'_, Arc<SimulationThread>>, path: String) -> Result<String, GameError> {
+    let book = Phrasebook::load(&path)?;
+    let language = book.language.clone();
+    log::info!("Loaded phrasebook {} ({})", path, language);
+    sim.call(move |server| server.set_phrasebook(book))?;
+    Ok(language)
 }
 
 /// Get server statistics
 #[tauri::command]
-fn get_stats(server: State<'_, Mutex<GameServer>>) -> Result<ServerStats, String> {
-    let server = server.lock().map_err(|e| e.to_string())?;
-    Ok(server.get_stats())
+fn get_stats(sim: State<'_, Arc<SimulationThread>>) -> Result<ServerStats, GameError> {
+    sim.call(|server| server.get_stats())
 }
 
 /// Get current game state
 #[tauri::command]
-fn get_game_state(server: State<'_, Mutex<GameServer>>) -> Result<GameState, String> {
-    let server = server.lock().map_err(|e| e.to_string())?;
-    Ok(server.get_state())
+fn get_game_state(sim: State<'_, Arc<SimulationThread>>) -> Result<GameState, GameError> {
+    sim.call(|server| server.get_state())
+}
+
+/// Get the commands the server will accept in its current state
+#[tauri::command]
+fn get_allowed_commands(sim: State<'_, Arc<SimulationThread>>) -> Result<Vec<GameCommand>, GameError> {
+    sim.call(|server| server.allowed_commands())
 }
 
 /// Pause the simulation
 #[tauri::command]
-fn pause_race(server: State<'_, Mutex<GameServer>>) -> Result<(), String> {
-    let mut server = server.lock().map_err(|e| e.to_string())?;
-    server.pause();
+fn pause_race(sim: State<'_, Arc<SimulationThread>>) -> Result<(), GameError> {
+    sim.control(SimCommand::Pause)?;
     log::info!("Race paused");
     Ok(())
 }
 
 /// Resume the simulation
 #[tauri::command]
-fn resume_race(server: State<'_, Mutex<GameServer>>) -> Result<(), String> {
-    let mut server = server.lock().map_err(|e| e.to_string())?;
-    server.resume();
+fn resume_race(sim: State<'_, Arc<SimulationThread>>) -> Result<(), GameError> {
+    sim.control(SimCommand::Resume)?;
     log::info!("Race resumed");
     Ok(())
 }
 
 /// Reset to idle state
 #[tauri::command]
-fn reset_race(server: State<'_, Mutex<GameServer>>) -> Result<(), String> {
-    let mut server = server.lock().map_err(|e| e.to_string())?;
-    server.reset();
+fn reset_race(sim: State<'_, Arc<SimulationThread>>) -> Result<(), GameError> {
+    sim.control(SimCommand::Reset)?;
     log::info!("Race reset");
     Ok(())
 }
 
+/// Record races from their start (takes effect from the next race)
+#[tauri::command]
+fn set_replay_recording(sim: State<'_, Arc<SimulationThread>>, enabled: bool) -> Result<(), GameError> {
+    sim.call(move |server| server.set_recording(enabled))
+}
+
+/// Save the current or last recorded race to a replay file
+#[tauri::command]
+fn save_replay(sim: State<'_, Arc<SimulationThread>>, path: String) -> Result<(), GameError> {
+    let replay = sim
+        .call(|server| server.get_replay().cloned())?
+        .ok_or(GameError::NoReplay)?;
+    replay.save(&path)?;
+    log::info!("Saved replay to {}", path);
+    Ok(())
+}
+
+/// Load a replay file and start playing it back
+#[tauri::command]
+fn load_replay(sim: State<'_, Arc<SimulationThread>>, path: String) -> Result<ReplayInfo, GameError> {
+    let replay = Replay::load(&path)?;
+    log::info!("Playing replay {} ({} frames)", path, replay.frames.len());
+    sim.call(move |server| server.load_replay(replay))?
+}
+
+/// Load an athlete roster file, returning its athletes
+#[tauri::command]
+fn load_roster(sim: State<'_, Arc<SimulationThread>>, path: String) -> Result<Vec<Athlete>, GameError> {
+    let roster = Roster::load(&path)?;
+    log::info!("Loaded roster {} ({} athletes)", path, roster.athletes.len());
+    let athletes = roster.athletes.clone();
+    sim.call(move |server| server.set_roster(roster))?;
+    Ok(athletes)
+}
+
+/// Get the athletes in the loaded roster
+#[tauri::command]
+fn list_roster(sim: State<'_, Arc<SimulationThread>>) -> Result<Vec<Athlete>, GameError> {
+    sim.call(|server| server.get_roster().map(|r| r.athletes.clone()).unwrap_or_default())
+}
+
+/// Pick which roster athletes run the next roster race (empty for a random draw)
+#[tauri::command]
+fn select_athletes(sim: State<'_, Arc<SimulationThread>>, bibs: Vec<u32>) -> Result<(), GameError> {
+    sim.call(move |server| server.select_athletes(bibs))?
+}
+
+/// Get who is running in the current race
+#[tauri::command]
+fn get_entrants(sim: State<'_, Arc<SimulationThread>>) -> Result<Vec<Entrant>, GameError> {
+    sim.call(|server| server.get_entrants())
+}
+
+/// Start a new series, replacing any in progress
+#[tauri::command]
+fn create_series(
+    sim: State<'_, Arc<SimulationThread>>,
+    name: String,
+    events: Vec<SeriesEvent>,
+    scoring: Option<Vec<u32>>,
+    tie_break: Option<TieBreak>,
+) -> Result<SeriesInfo, GameError> {
+    let series = Series::new(name, events, scoring, tie_break.unwrap_or_default())
+        .map_err(GameError::InvalidSeries)?;
+    let info = series.info();
+    sim.call(move |server| server.start_series(series))?;
+    log::info!("Series '{}' started with {} races", info.name, info.race_count);
+    Ok(info)
+}
+
+/// Get the progress of the series in progress
+#[tauri::command]
+fn get_series(sim: State<'_, Arc<SimulationThread>>) -> Result<Option<SeriesInfo>, GameError> {
+    sim.call(|server| server.get_series().map(|s| s.info()))
+}
+
+/// Get the series standings, leader first
+#[tauri::command]
+fn get_standings(sim: State<'_, Arc<SimulationThread>>) -> Result<Vec<Standing>, GameError> {
+    sim.call(|server| server.get_series().map(|s| s.standings()).unwrap_or_default())
+}
+
+/// Get the series races still to run, next first
+#[tauri::command]
+fn get_upcoming_races(sim: State<'_, Arc<SimulationThread>>) -> Result<Vec<SeriesEvent>, GameError> {
+    sim.call(|server| server.get_series().map(|s| s.upcoming().to_vec()).unwrap_or_default())
+}
+
+/// Initialize the next series race. Distance, field size and seed come from the
+/// series calendar; the field is drawn from the loaded roster if there is one.
+#[tauri::command]
+fn init_series_race(
+    sim: State<'_, Arc<SimulationThread>>,
+    time_scale: Option<f32>,
+    world_positions: Option<bool>,
+    start_formation: Option<StartFormation>,
+    player_name: Option<String>,
+    timing_points: Option<TimingPoints>,
+    pacing_mix: Option<PacingMix>,
+    variability: Option<f32>,
+    player_target_time: Option<f32>,
+) -> Result<SeriesEvent, GameError> {
+    let defaults = RaceConfig::default();
+    let config = RaceConfig {
+        time_scale: time_scale.unwrap_or(defaults.time_scale),
+        world_positions: world_positions.unwrap_or(defaults.world_positions),
+        start_formation: start_formation.unwrap_or(defaults.start_formation),
+        player: player_name.map(|name| PlayerConfig { name, target_time: player_target_time }),
+        timing_points,
+        pacing_mix,
+        variability,
+        ..defaults
+    };
+
+    let event = sim.call(move |server| server.init_series_race(config))??;
+    log::info!("Series race '{}' initialized ({}m)", event.name, event.distance);
+    Ok(event)
+}
+
+/// Abandon the series in progress
+#[tauri::command]
+fn end_series(sim: State<'_, Arc<SimulationThread>>) -> Result<(), GameError> {
+    sim.call(|server| server.end_series())?;
+    log::info!("Series ended");
+    Ok(())
+}
+
+/// Run a query against the results database on the simulation thread
+fn query_results<R: Send + 'static>(
+    sim: &SimulationThread,
+    query: impl FnOnce(&ResultsDb) -> rusqlite::Result<R> + Send + 'static,
+) -> Result<R, GameError> {
+    sim.call(move |server| {
+        let db = server.get_results_db().ok_or(GameError::NoResultsDb)?;
+        Ok(query(db)?)
+    })?
+}
+
+/// Get an athlete's best time at every distance they have raced
+#[tauri::command]
+fn get_personal_bests(sim: State<'_, Arc<SimulationThread>>, athlete: AthleteId) -> Result<Vec<Mark>, GameError> {
+    query_results(&sim, move |db| db.personal_bests(&athlete))
+}
+
+/// Get an athlete's best time at every distance in a season (the current year by default)
+#[tauri::command]
+fn get_season_bests(
+    sim: State<'_, Arc<SimulationThread>>,
+    athlete: AthleteId,
+    season: Option<i32>,
+) -> Result<Vec<Mark>, GameError> {
+    query_results(&sim, move |db| db.season_bests(&athlete, season))
+}
+
+/// Get the fastest time at every distance, at one meet or in any race
+#[tauri::command]
+fn get_records(sim: State<'_, Arc<SimulationThread>>, meet: Option<String>) -> Result<Vec<Mark>, GameError> {
+    query_results(&sim, move |db| db.records(meet.as_deref()))
+}
+
+/// Get an athlete's stored results, newest first
+#[tauri::command]
+fn get_race_history(
+    sim: State<'_, Arc<SimulationThread>>,
+    athlete: AthleteId,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryEntry>, GameError> {
+    query_results(&sim, move |db| db.history(&athlete, limit.unwrap_or(50)))
+}
+
+/// Get stored races, newest first
+#[tauri::command]
+fn get_recent_races(sim: State<'_, Arc<SimulationThread>>, limit: Option<u32>) -> Result<Vec<RaceSummary>, GameError> {
+    query_results(&sim, move |db| db.recent_races(limit.unwrap_or(50)))
+}
+
+/// Jump to a position in the loaded replay (seconds)
+#[tauri::command]
+fn seek_replay(sim: State<'_, Arc<SimulationThread>>, time: f32) -> Result<(), GameError> {
+    sim.call(move |server| server.seek_replay(time))?
+}
+
+/// Set the replay playback rate (negative rewinds)
+#[tauri::command]
+fn set_replay_speed(sim: State<'_, Arc<SimulationThread>>, speed: f32) -> Result<(), GameError> {
+    sim.call(move |server| server.set_replay_speed(speed))?
+}
+
+/// Get playback position and status of the loaded replay
+#[tauri::command]
+fn get_replay_info(sim: State<'_, Arc<SimulationThread>>) -> Result<Option<ReplayInfo>, GameError> {
+    sim.call(|server| server.get_replay_info())
+}
+
+/// Host a multiplayer race over WebSocket. Returns the address clients should connect to.
+/// The race replaces the local one and runs on the same simulation thread.
+#[tauri::command]
+fn host_race(
+    sim: State<'_, Arc<SimulationThread>>,
+    host: State<'_, Mutex<Option<NetServer>>>,
+    address: Option<String>,
+    runner_count: Option<u32>,
+    distance: Option<f32>,
+    seed: Option<u64>,
+) -> Result<String, GameError> {
+    let mut host = host.lock().map_err(|_| GameError::Unavailable)?;
+
+    let defaults = RaceConfig::default();
+    let config = RaceConfig {
+        runner_count: runner_count.unwrap_or(defaults.runner_count),
+        distance: distance.unwrap_or(defaults.distance),
+        time_scale: 1.0,
+        seed,
+        lobby: true,
+        ..defaults
+    };
+
+    sim.call(move |server| server.init_race(config))??;
+
+    // Stop any previous host first so its port is free again
+    host.take();
+    let address = address.unwrap_or_else(|| "127.0.0.1:9001".to_string());
+    let net = NetServer::start(sim.inner().clone(), address.as_str())?;
+    let url = format!("ws://{}", net.local_addr());
+    *host = Some(net);
+
+    log::info!("Hosting multiplayer race at {}", url);
+    Ok(url)
+}
+
+/// Start the hosted multiplayer race
+#[tauri::command]
+fn start_hosted_race(
+    sim: State<'_, Arc<SimulationThread>>,
+    host: State<'_, Mutex<Option<NetServer>>>,
+) -> Result<(), GameError> {
+    let hosting = host.lock().map_err(|_| GameError::Unavailable)?.is_some();
+    if !hosting {
+        return Err(GameError::NotHosting);
+    }
+    sim.control(SimCommand::Start)?;
+    log::info!("Hosted race started");
+    Ok(())
+}
+
+/// Stop hosting and disconnect all clients
+#[tauri::command]
+fn stop_hosting(host: State<'_, Mutex<Option<NetServer>>>) -> Result<(), GameError> {
+    let mut host = host.lock().map_err(|_| GameError::Unavailable)?;
+    *host = None;
+    log::info!("Stopped hosting");
+    Ok(())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .manage(Mutex::new(GameServer::new()))
+        .manage(Mutex::new(None::<NetServer>))
+        .manage(SnapshotFeeds::default())
         .setup(|app| {
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -113,20 +506,88 @@ pub fn run() {
                         .build(),
                 )?;
             }
+
+            // Series standings and past results live in the app data dir so they survive restarts
+            let data_dir = app.path().app_data_dir()?;
+            let mut server = GameServer::new();
+            server.open_series(data_dir.join(SERIES_FILE));
+            server.open_results(data_dir.join(RESULTS_FILE));
+
+            // Start with the bundled athletes; load_roster replaces them
+            let roster = app
+                .path()
+                .resolve(DEFAULT_ROSTER, BaseDirectory::Resource)
+                .map_err(|e| e.to_string())
+                .and_then(|path| Roster::load(path).map_err(|e| e.to_string()));
+            match roster {
+                Ok(roster) => server.set_roster(roster),
+                Err(e) => log::warn!("Failed to load default roster: {}", e),
+            }
+
+            // Push to the frontend instead of waiting to be polled. Runner state
+            // goes to each window as binary frames (subscribe_snapshots).
+            let handle = app.handle().clone();
+            let sim = SimulationThread::spawn(server, move |event| {
+                let emitted = match event {
+                    SimEvent::Snapshot(_) => Ok(()),
+                    SimEvent::Events(events) => handle.emit(EVENTS_EVENT, events),
+                    SimEvent::Status(state) => handle.emit(STATUS_EVENT, state),
+                    SimEvent::Commentary(lines) => handle.emit(COMMENTARY_EVENT, lines),
+                    // Fetched with get_results once the status says the race is over
+                    SimEvent::Results(_) => Ok(()),
+                };
+                if let Err(e) = emitted {
+                    log::warn!("Failed to emit simulation event: {}", e);
+                }
+            });
+            app.manage(Arc::new(sim));
+
             log::info!("Track Runner game server initialized");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             init_race,
             start_race,
+            set_player_input,
             tick,
+            subscribe_snapshots,
+            ack_snapshot,
+            unsubscribe_snapshots,
             get_snapshot,
             get_results,
+            get_splits,
+            get_commentary,
+            load_phrasebook,
             get_stats,
             get_game_state,
+            get_allowed_commands,
             pause_race,
             resume_race,
             reset_race,
+            set_replay_recording,
+            save_replay,
+            load_replay,
+            seek_replay,
+            set_replay_speed,
+            get_replay_info,
+            load_roster,
+            list_roster,
+            select_athletes,
+            get_entrants,
+            create_series,
+            get_series,
+            get_standings,
+            get_upcoming_races,
+            init_series_race,
+            end_series,
+            get_personal_bests,
+            get_season_bests,
+            get_records,
+            get_race_history,
+            get_recent_races,
+            host_race,
+            start_hosted_race,
+            stop_hosting,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -5,10 +5,46 @@
  * Rust game server running in the Tauri backend.
  */
 
+import { SnapshotDecoder } from './SnapshotDecoder';
+
 // Types matching Rust structs
 
 export type RaceStatus = 'NotStarted' | 'Countdown' | 'Racing' | 'Finished';
-export type GameState = 'Idle' | 'Loading' | 'Ready' | 'Racing' | 'Results';
+export type GameState = 'Idle' | 'Loading' | 'Ready' | 'Racing' | 'Paused' | 'Results' | 'Replay';
+
+// Commands whose availability depends on the game state (see getAllowedCommands)
+export type GameCommand =
+  | 'init_race'
+  | 'init_series_race'
+  | 'start_race'
+  | 'pause_race'
+  | 'resume_race'
+  | 'reset_race'
+  | 'set_player_input'
+  | 'save_replay'
+  | 'load_replay'
+  | 'seek_replay'
+  | 'set_replay_speed';
+
+export type StartFormation = 'Waterfall' | 'Alley' | 'LaneStagger';
+export type TrackSection = 'Straight' | 'Bend';
+
+export type SplitInterval = 'Lap' | 'Kilometer' | 'Mile' | { Custom: number };
+
+export type PacingStrategy = 'Even' | 'NegativeSplit' | 'PositiveSplit' | 'FrontRunner' | 'SitAndKick' | 'Surger';
+
+// Relative weight of each strategy among AI runners
+export type PacingMix = Partial<Record<PacingStrategy, number>>;
+
+// Where actual split times are recorded; the finish is always included
+export type TimingPoints = { Every: SplitInterval } | { Mats: number[] };
+
+export interface TrackPosition {
+  x: number;
+  z: number;
+  rotation: number;
+  section: TrackSection;
+}
 
 export interface RunnerSnapshot {
   id: number;
@@ -17,6 +53,18 @@ export interface RunnerSnapshot {
   speed: number;
   animation_phase: number;
   finished: boolean;
+  drafting: boolean;
+  energy: number; // 0-100: lactic acid level, stamina, or kick bar
+  kicking: boolean;
+  player: boolean;
+  lane: number;
+  finish_distance: number; // Lane-corrected: race distance + stagger
+  place: number; // 1 = leading, 0 before the gun
+  gap: number; // Meters behind the leader
+  gap_time: number; // Race seconds behind the leader
+  interval: number; // Meters behind the runner one place ahead
+  interval_time: number; // Race seconds behind the runner one place ahead
+  world?: TrackPosition; // Only present when the race was initialized with worldPositions
 }
 
 export interface RaceSnapshot {
@@ -27,11 +75,212 @@ export interface RaceSnapshot {
   finisher_count: number;
 }
 
+export interface TickResult {
+  snapshot: RaceSnapshot;
+  alpha: number; // 0-1 progress toward the next fixed step, for interpolation
+  steps: number;
+  events: RaceEvent[]; // What happened during those steps, oldest first (always empty; see onRaceEvents)
+  place_changes: PlaceChange[]; // Runners whose place changed during those steps (always empty; see onRaceEvents)
+}
+
+// What happened during a tick, pushed only when something did
+export interface RaceEvents {
+  events: RaceEvent[];
+  place_changes: PlaceChange[];
+}
+
+export interface PlaceChange {
+  runner_id: number;
+  from: number;
+  to: number;
+}
+
+// Something that happened during a race; times are race seconds
+export type RaceEvent =
+  | { type: 'race_start'; time: number }
+  | { type: 'lead_change'; time: number; runner_id: number; previous_id: number; to_go: number }
+  | { type: 'pass'; time: number; runner_id: number; passed_id: number; place: number; to_go: number }
+  | { type: 'lap_completed'; time: number; runner_id: number; laps_to_go: number }
+  | { type: 'bell_lap'; time: number; runner_id: number }
+  | { type: 'split_crossed'; time: number; runner_id: number; distance: number; split: number; place: number }
+  | { type: 'runner_finished'; time: number; runner_id: number; position: number }
+  | { type: 'race_finished'; time: number; winner_id: number | null };
+
+export type Phrase =
+  | 'race_start'
+  | 'takes_lead'
+  | 'pass'
+  | 'bell_lap'
+  | 'gap'
+  | 'chase_pack'
+  | 'winner'
+  | 'finisher'
+  | 'race_finished';
+
+export interface CommentaryLine {
+  time: number; // Race seconds
+  phrase: Phrase;
+  text: string;
+}
+
+// Multiplayer WebSocket protocol (JSON text frames)
+export type ClientMessage =
+  | { type: 'join'; name: string }
+  | { type: 'input'; desired_speed: number; lane_change: number }
+  | { type: 'leave' };
+
+export type ServerMessage =
+  | { type: 'joined'; runner_id: number }
+  | { type: 'snapshot'; snapshot: RaceSnapshot }
+  | { type: 'results'; results: RaceResult[] }
+  | { type: 'error'; message: string };
+
+export interface RecordedSplit {
+  distance: number; // Timing point distance from the start
+  time: number; // Race time when crossed
+  split: number; // Time since the previous timing point
+}
+
+export interface RunnerSplits {
+  runner_id: number;
+  splits: RecordedSplit[];
+}
+
 export interface RaceResult {
   runner_id: number;
   runner_name: string;
   finish_time: number;
   position: number;
+  splits: RecordedSplit[];
+  strategy: PacingStrategy;
+  bib: number | null; // Roster bib, null for anonymous runners
+  player: boolean;
+  flags: ResultFlag[]; // Set once the race is stored in the results database
+}
+
+export type ResultFlag = 'PersonalBest' | 'SeasonBest' | 'CourseRecord' | 'MeetRecord';
+
+// Who a stored result belongs to: a roster bib or the human player's name
+export type AthleteId = { Bib: number } | { Player: string };
+
+export interface Mark {
+  runner_name: string;
+  bib: number | null;
+  distance: number; // Meters
+  time: number; // Seconds
+  race_id: number;
+  recorded_at: number; // Unix seconds
+  meet: string | null;
+}
+
+export interface RaceSummary {
+  race_id: number;
+  recorded_at: number; // Unix seconds
+  distance: number;
+  seed: number;
+  runner_count: number;
+  meet: string | null; // Series name for series races
+  winner: string | null;
+  winning_time: number | null;
+}
+
+export interface HistoryEntry {
+  race_id: number;
+  recorded_at: number; // Unix seconds
+  distance: number;
+  meet: string | null;
+  position: number;
+  finish_time: number;
+  flags: ResultFlag[];
+}
+
+export interface Kit {
+  primary: string; // CSS colour
+  secondary: string | null;
+}
+
+export interface PersonalBest {
+  distance: number; // Meters
+  time: number; // Seconds
+}
+
+export interface Athlete {
+  name: string;
+  bib: number;
+  nationality: string | null;
+  kit: Kit | null;
+  model: string | null; // Character model key (see CharacterPreview.js)
+  pbs: PersonalBest[];
+  pacing: PacingMix | null;
+}
+
+export interface Entrant {
+  runner_id: number;
+  name: string;
+  strategy: PacingStrategy;
+  athlete: Athlete | null; // Null for anonymous runners
+}
+
+// How runners level on points are separated
+export type TieBreak = 'Countback' | 'LastRace';
+
+export interface SeriesEvent {
+  name: string;
+  distance: number; // Meters
+  runner_count?: number | null; // Race default if not set
+  seed?: number | null; // Random if not set
+}
+
+export interface SeriesInfo {
+  name: string;
+  race_count: number;
+  races_completed: number;
+  next: SeriesEvent | null; // Null once the series is over
+  scoring: number[]; // Points for 1st, 2nd, 3rd...
+  tie_break: TieBreak;
+}
+
+export interface Standing {
+  rank: number; // Shared when the tie-break can't separate runners
+  name: string;
+  bib: number | null;
+  points: number;
+  races: number;
+  wins: number;
+  positions: (number | null)[]; // Place in each completed race
+}
+
+export interface ReplayInfo {
+  seed: number;
+  distance: number;
+  duration: number; // Seconds
+  time: number; // Playback position (seconds)
+  speed: number; // Playback rate, negative rewinds
+  frame_count: number;
+}
+
+// What every command rejects with
+export type GameErrorCode =
+  | 'invalid_config'
+  | 'invalid_state'
+  | 'no_race'
+  | 'no_runners'
+  | 'no_player'
+  | 'no_replay'
+  | 'no_roster'
+  | 'unknown_athlete'
+  | 'no_series'
+  | 'series_complete'
+  | 'invalid_series'
+  | 'no_results_db'
+  | 'not_hosting'
+  | 'io'
+  | 'database'
+  | 'unavailable';
+
+export interface GameError {
+  code: GameErrorCode;
+  message: string;
 }
 
 export interface ServerStats {
@@ -55,6 +304,13 @@ async function getInvoke(): Promise<typeof import('@tauri-apps/api/core').invoke
   return invoke;
 }
 
+// Events pushed by the Rust simulation thread
+const EVENTS_EVENT = 'race-events';
+const STATUS_EVENT = 'race-status';
+const COMMENTARY_EVENT = 'race-commentary';
+
+export type Unsubscribe = () => void;
+
 /**
  * GameServerBridge - Interface to Rust game server
  */
@@ -89,11 +345,37 @@ export class GameServerBridge {
   }
 
   /**
-   * Initialize a new race
+   * Initialize a new race (same seed = same race)
    */
-  async initRace(runnerCount?: number, timeScale?: number): Promise<void> {
+  async initRace(
+    runnerCount?: number,
+    timeScale?: number,
+    seed?: number,
+    distance?: number,
+    worldPositions?: boolean,
+    startFormation?: StartFormation,
+    playerName?: string,
+    timingPoints?: TimingPoints,
+    pacingMix?: PacingMix,
+    variability?: number, // Surge/bad patch intensity, 1 by default, 0 turns it off
+    useRoster?: boolean, // Draw the field from the loaded roster
+    playerTargetTime?: number // Player's own finish time (s); fatigue is measured against it
+  ): Promise<void> {
     if (!this.invoke) throw new Error('Bridge not initialized');
-    await this.invoke('init_race', { runner_count: runnerCount, time_scale: timeScale });
+    await this.invoke('init_race', {
+      runner_count: runnerCount,
+      time_scale: timeScale,
+      seed,
+      distance,
+      world_positions: worldPositions,
+      start_formation: startFormation,
+      player_name: playerName,
+      timing_points: timingPoints,
+      pacing_mix: pacingMix,
+      variability,
+      use_roster: useRoster,
+      player_target_time: playerTargetTime,
+    });
   }
 
   /**
@@ -105,13 +387,84 @@ export class GameServerBridge {
   }
 
   /**
-   * Perform a simulation tick and return current state
+   * Send the player's desired speed (m/s) and lane change (-1 inside, +1 outside)
    */
-  async tick(): Promise<RaceSnapshot | null> {
+  async setPlayerInput(desiredSpeed: number, laneChange: number): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('set_player_input', { desired_speed: desiredSpeed, lane_change: laneChange });
+  }
+
+  /**
+   * Get the current state for polling clients. Doesn't advance the race;
+   * events and place changes only arrive through onRaceEvents.
+   */
+  async tick(): Promise<TickResult | null> {
     if (!this.invoke) throw new Error('Bridge not initialized');
     return await this.invoke('tick');
   }
 
+  /**
+   * Subscribe to the snapshot pushed after every simulation tick, sent as
+   * binary delta-compressed frames (see SNAPSHOT_FORMAT.md). The simulation
+   * runs on its own thread, so there's no need to call tick. Each window
+   * has one feed: subscribing again replaces it.
+   */
+  async onSnapshot(callback: (result: TickResult) => void): Promise<Unsubscribe> {
+    const invoke = this.invoke;
+    if (!invoke) throw new Error('Bridge not initialized');
+    const { Channel } = await import('@tauri-apps/api/core');
+
+    const decoder = new SnapshotDecoder();
+    const frames = new Channel<ArrayBuffer>();
+    frames.onmessage = (frame) => {
+      try {
+        const decoded = decoder.decode(frame);
+        invoke('ack_snapshot', { sequence: decoded.sequence }).catch(() => {});
+        callback(decoded.tick);
+      } catch (e) {
+        // Ask for a keyframe
+        console.warn('GameServerBridge: dropped snapshot frame:', e);
+        decoder.reset();
+        invoke('ack_snapshot', { sequence: null }).catch(() => {});
+      }
+    };
+
+    await invoke('subscribe_snapshots', { frames });
+    return () => {
+      frames.onmessage = () => {};
+      invoke('unsubscribe_snapshots').catch(() => {});
+    };
+  }
+
+  /**
+   * Subscribe to race events and place changes, pushed after any tick in
+   * which something happened
+   */
+  async onRaceEvents(callback: (events: RaceEvents) => void): Promise<Unsubscribe> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    const { listen } = await import('@tauri-apps/api/event');
+    return await listen<RaceEvents>(EVENTS_EVENT, (event) => callback(event.payload));
+  }
+
+  /**
+   * Subscribe to game state changes. Every state entered is delivered in
+   * order, including passing ones like Loading.
+   */
+  async onStatus(callback: (state: GameState) => void): Promise<Unsubscribe> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    const { listen } = await import('@tauri-apps/api/event');
+    return await listen<GameState>(STATUS_EVENT, (event) => callback(event.payload));
+  }
+
+  /**
+   * Subscribe to commentary lines as they are said
+   */
+  async onCommentary(callback: (lines: CommentaryLine[]) => void): Promise<Unsubscribe> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    const { listen } = await import('@tauri-apps/api/event');
+    return await listen<CommentaryLine[]>(COMMENTARY_EVENT, (event) => callback(event.payload));
+  }
+
   /**
    * Get current race snapshot without advancing simulation
    */
@@ -128,6 +481,30 @@ export class GameServerBridge {
     return await this.invoke('get_results');
   }
 
+  /**
+   * Get actual split times recorded so far, for one runner or every runner
+   */
+  async getSplits(runnerId?: number): Promise<RunnerSplits[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_splits', { runner_id: runnerId });
+  }
+
+  /**
+   * Get the commentary of the current race, optionally only lines after race time `since`
+   */
+  async getCommentary(since?: number): Promise<CommentaryLine[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_commentary', { since });
+  }
+
+  /**
+   * Load a commentary phrasebook file, returning its language
+   */
+  async loadPhrasebook(path: string): Promise<string> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('load_phrasebook', { path });
+  }
+
   /**
    * Get server statistics
    */
@@ -144,6 +521,14 @@ export class GameServerBridge {
     return await this.invoke('get_game_state');
   }
 
+  /**
+   * Get the commands the server will accept right now, e.g. to grey out buttons
+   */
+  async getAllowedCommands(): Promise<GameCommand[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_allowed_commands');
+  }
+
   /**
    * Pause the simulation
    */
@@ -167,6 +552,224 @@ export class GameServerBridge {
     if (!this.invoke) throw new Error('Bridge not initialized');
     await this.invoke('reset_race');
   }
+
+  /**
+   * Record races from their start (takes effect from the next race)
+   */
+  async setReplayRecording(enabled: boolean): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('set_replay_recording', { enabled });
+  }
+
+  /**
+   * Save the current or last recorded race to a replay file
+   */
+  async saveReplay(path: string): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('save_replay', { path });
+  }
+
+  /**
+   * Load a replay file and start playing it back through tick/getSnapshot.
+   * Use pauseRace/resumeRace to pause playback.
+   */
+  async loadReplay(path: string): Promise<ReplayInfo> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('load_replay', { path });
+  }
+
+  /**
+   * Jump to a position in the loaded replay (seconds)
+   */
+  async seekReplay(time: number): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('seek_replay', { time });
+  }
+
+  /**
+   * Set the replay playback rate (negative rewinds)
+   */
+  async setReplaySpeed(speed: number): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('set_replay_speed', { speed });
+  }
+
+  /**
+   * Get playback position and status of the loaded replay
+   */
+  async getReplayInfo(): Promise<ReplayInfo | null> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_replay_info');
+  }
+
+  /**
+   * Load an athlete roster file, returns its athletes
+   */
+  async loadRoster(path: string): Promise<Athlete[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('load_roster', { path });
+  }
+
+  /**
+   * Get the athletes in the loaded roster
+   */
+  async listRoster(): Promise<Athlete[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('list_roster');
+  }
+
+  /**
+   * Pick which roster athletes run the next roster race (empty for a random draw)
+   */
+  async selectAthletes(bibs: number[]): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('select_athletes', { bibs });
+  }
+
+  /**
+   * Get who is running in the current race
+   */
+  async getEntrants(): Promise<Entrant[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_entrants');
+  }
+
+  /**
+   * Start a new series, replacing any in progress. Standings are saved after
+   * every series race and picked up again on the next launch.
+   */
+  async createSeries(
+    name: string,
+    events: SeriesEvent[],
+    scoring?: number[],
+    tieBreak?: TieBreak
+  ): Promise<SeriesInfo> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('create_series', { name, events, scoring, tie_break: tieBreak });
+  }
+
+  /**
+   * Get the progress of the series in progress
+   */
+  async getSeries(): Promise<SeriesInfo | null> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_series');
+  }
+
+  /**
+   * Get the series standings, leader first
+   */
+  async getStandings(): Promise<Standing[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_standings');
+  }
+
+  /**
+   * Get the series races still to run, next first
+   */
+  async getUpcomingRaces(): Promise<SeriesEvent[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_upcoming_races');
+  }
+
+  /**
+   * Initialize the next series race (distance, field size and seed come from
+   * the calendar; the field is drawn from the loaded roster if there is one)
+   */
+  async initSeriesRace(
+    timeScale?: number,
+    worldPositions?: boolean,
+    startFormation?: StartFormation,
+    playerName?: string,
+    timingPoints?: TimingPoints,
+    pacingMix?: PacingMix,
+    variability?: number,
+    playerTargetTime?: number // Player's own finish time (s); fatigue is measured against it
+  ): Promise<SeriesEvent> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('init_series_race', {
+      time_scale: timeScale,
+      world_positions: worldPositions,
+      start_formation: startFormation,
+      player_name: playerName,
+      timing_points: timingPoints,
+      pacing_mix: pacingMix,
+      variability,
+      player_target_time: playerTargetTime,
+    });
+  }
+
+  /**
+   * Abandon the series in progress
+   */
+  async endSeries(): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('end_series');
+  }
+
+  /**
+   * Get an athlete's best time at every distance they have raced
+   */
+  async getPersonalBests(athlete: AthleteId): Promise<Mark[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_personal_bests', { athlete });
+  }
+
+  /**
+   * Get an athlete's best time at every distance in a season (current year by default)
+   */
+  async getSeasonBests(athlete: AthleteId, season?: number): Promise<Mark[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_season_bests', { athlete, season });
+  }
+
+  /**
+   * Get the fastest time at every distance, at one meet or in any race
+   */
+  async getRecords(meet?: string): Promise<Mark[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_records', { meet });
+  }
+
+  /**
+   * Get an athlete's stored results, newest first
+   */
+  async getRaceHistory(athlete: AthleteId, limit?: number): Promise<HistoryEntry[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_race_history', { athlete, limit });
+  }
+
+  /**
+   * Get stored races, newest first
+   */
+  async getRecentRaces(limit?: number): Promise<RaceSummary[]> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('get_recent_races', { limit });
+  }
+
+  /**
+   * Host a multiplayer race over WebSocket, returns the ws:// URL clients join
+   */
+  async hostRace(address?: string, runnerCount?: number, distance?: number, seed?: number): Promise<string> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    return await this.invoke('host_race', { address, runner_count: runnerCount, distance, seed });
+  }
+
+  /**
+   * Start the hosted multiplayer race
+   */
+  async startHostedRace(): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('start_hosted_race');
+  }
+
+  /**
+   * Stop hosting and disconnect all clients
+   */
+  async stopHosting(): Promise<void> {
+    if (!this.invoke) throw new Error('Bridge not initialized');
+    await this.invoke('stop_hosting');
+  }
 }
 
 // Singleton instance
@@ -8,7 +8,7 @@
 
 import * as THREE from 'three';
 import type { Components } from '../core/components';
-import type { RaceSnapshot, RunnerSnapshot } from './GameServerBridge';
+import type { RaceSnapshot, RunnerSnapshot, Unsubscribe } from './GameServerBridge';
 import { GameServerBridge, getGameServerBridge } from './GameServerBridge';
 
 export interface AdapterConfig {
@@ -28,7 +28,7 @@ export class RustWorldAdapter {
   private bridge: GameServerBridge;
   private config: AdapterConfig;
   private lastSnapshot: RaceSnapshot | null = null;
-  private tickInterval: number | null = null;
+  private unlisten: Unsubscribe | null = null;
   private onUpdate: ((snapshot: RaceSnapshot) => void) | null = null;
 
   // For interpolation between snapshots
@@ -71,34 +71,32 @@ export class RustWorldAdapter {
   }
 
   /**
-   * Start the tick loop
+   * Start receiving snapshots. The Rust simulation thread ticks on its own
+   * and pushes a snapshot event every tick.
    */
-  startTickLoop(onUpdate?: (snapshot: RaceSnapshot) => void): void {
+  async startTickLoop(onUpdate?: (snapshot: RaceSnapshot) => void): Promise<void> {
+    this.stopTickLoop();
     this.onUpdate = onUpdate || null;
-    const tickMs = 1000 / this.config.tickRate;
-
-    this.tickInterval = window.setInterval(async () => {
-      try {
-        this.prevSnapshot = this.lastSnapshot;
-        this.lastSnapshot = await this.bridge.tick();
-        this.snapshotTime = performance.now();
-
-        if (this.lastSnapshot && this.onUpdate) {
-          this.onUpdate(this.lastSnapshot);
-        }
-      } catch (e) {
-        console.error('Tick error:', e);
+
+    this.unlisten = await this.bridge.onSnapshot((result) => {
+      this.prevSnapshot = this.lastSnapshot;
+      this.lastSnapshot = result.snapshot;
+      this.interpolationFactor = result.alpha;
+      this.snapshotTime = performance.now();
+
+      if (this.onUpdate) {
+        this.onUpdate(result.snapshot);
       }
-    }, tickMs);
+    });
   }
 
   /**
-   * Stop the tick loop
+   * Stop receiving snapshots
    */
   stopTickLoop(): void {
-    if (this.tickInterval !== null) {
-      clearInterval(this.tickInterval);
-      this.tickInterval = null;
+    if (this.unlisten) {
+      this.unlisten();
+      this.unlisten = null;
     }
   }
 
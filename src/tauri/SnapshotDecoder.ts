@@ -0,0 +1,210 @@
+/**
+ * SnapshotDecoder - Decodes the binary snapshot frames pushed by subscribe_snapshots
+ *
+ * Implements the format described in SNAPSHOT_FORMAT.md. Keeps the
+ * quantized runners of recent frames so delta frames can be applied.
+ */
+
+import type { RaceSnapshot, RaceStatus, RunnerSnapshot, TickResult } from './GameServerBridge';
+
+const WIRE_VERSION = 2;
+const FRAME_KEY = 0;
+const FRAME_DELTA = 1;
+const HISTORY_LEN = 120;
+
+const FIELD_COUNT = 16;
+const STATUSES: RaceStatus[] = ['NotStarted', 'Countdown', 'Racing', 'Finished'];
+
+// Quantization scale per field, in wire order
+const SCALES = [100, 1000, 100, 1000, 10, 1, 1, 100, 100, 100, 1000, 1, 100, 100, 100, 100];
+
+const FLAG_FINISHED = 1 << 0;
+const FLAG_DRAFTING = 1 << 1;
+const FLAG_KICKING = 1 << 2;
+const FLAG_PLAYER = 1 << 3;
+const FLAG_WORLD = 1 << 4;
+const FLAG_BEND = 1 << 5;
+
+interface QuantizedRunner {
+  id: number;
+  fields: number[];
+}
+
+export interface DecodedFrame {
+  sequence: number; // Pass back with ack_snapshot
+  tick: TickResult;
+}
+
+/**
+ * Cursor over a frame. Values stay within Number's integer range, so
+ * arithmetic is used instead of 32-bit bitwise operators.
+ */
+class Reader {
+  private pos = 0;
+  private view: DataView;
+
+  constructor(private bytes: Uint8Array) {
+    this.view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  }
+
+  get done(): boolean {
+    return this.pos === this.bytes.length;
+  }
+
+  u8(): number {
+    if (this.pos >= this.bytes.length) throw new Error('Truncated snapshot');
+    return this.bytes[this.pos++];
+  }
+
+  f32(): number {
+    if (this.pos + 4 > this.bytes.length) throw new Error('Truncated snapshot');
+    const value = this.view.getFloat32(this.pos, true);
+    this.pos += 4;
+    return value;
+  }
+
+  varint(): number {
+    let value = 0;
+    let scale = 1;
+    for (let i = 0; i < 8; i++) {
+      const byte = this.u8();
+      value += (byte & 0x7f) * scale;
+      if ((byte & 0x80) === 0) return value;
+      scale *= 128;
+    }
+    throw new Error('Varint too long');
+  }
+
+  svarint(): number {
+    const value = this.varint();
+    return value % 2 === 0 ? value / 2 : -(value + 1) / 2;
+  }
+}
+
+export class SnapshotDecoder {
+  private history: { sequence: number; runners: QuantizedRunner[] }[] = [];
+
+  /**
+   * Decode a frame. Throws if it is malformed or its delta base is unknown,
+   * in which case the caller should request a keyframe (omit ack).
+   */
+  decode(buffer: ArrayBuffer | Uint8Array): DecodedFrame {
+    const reader = new Reader(buffer instanceof Uint8Array ? buffer : new Uint8Array(buffer));
+
+    const version = reader.u8();
+    if (version !== WIRE_VERSION) throw new Error(`Unsupported snapshot version ${version}`);
+
+    const kind = reader.u8();
+    const sequence = reader.varint();
+    let baseSequence: number | null = null;
+    if (kind === FRAME_DELTA) {
+      baseSequence = reader.varint();
+    } else if (kind !== FRAME_KEY) {
+      throw new Error(`Unknown frame kind ${kind}`);
+    }
+
+    const status = STATUSES[reader.u8()];
+    if (!status) throw new Error('Unknown race status');
+    const elapsedTime = reader.f32();
+    const countdown = reader.f32();
+    const alpha = reader.f32();
+    const steps = reader.varint();
+    const finisherCount = reader.varint();
+    const runnerCount = reader.varint();
+
+    let runners: QuantizedRunner[];
+    if (baseSequence !== null) {
+      const base = this.history.find((h) => h.sequence === baseSequence);
+      if (!base) throw new Error(`Missing delta base ${baseSequence}`);
+      if (base.runners.length !== runnerCount) throw new Error('Delta base runner count mismatch');
+
+      runners = base.runners.map((r) => ({ id: r.id, fields: r.fields.slice() }));
+      const recordCount = reader.varint();
+      for (let i = 0; i < recordCount; i++) {
+        const runner = runners[reader.varint()];
+        if (!runner) throw new Error('Runner index out of range');
+        readFields(reader, runner.fields);
+      }
+    } else {
+      runners = [];
+      for (let i = 0; i < runnerCount; i++) {
+        const id = reader.varint();
+        const fields = new Array<number>(FIELD_COUNT).fill(0);
+        readFields(reader, fields);
+        runners.push({ id, fields });
+      }
+    }
+
+    if (!reader.done) throw new Error('Trailing bytes after snapshot');
+
+    this.history.push({ sequence, runners });
+    if (this.history.length > HISTORY_LEN) this.history.shift();
+
+    const snapshot: RaceSnapshot = {
+      status,
+      elapsed_time: elapsedTime,
+      countdown,
+      runners: runners.map(toSnapshot),
+      finisher_count: finisherCount,
+    };
+
+    // Events and place changes arrive separately, in race-events
+    return { sequence, tick: { snapshot, alpha, steps, events: [], place_changes: [] } };
+  }
+
+  /**
+   * Forget all delta bases
+   */
+  reset(): void {
+    this.history = [];
+  }
+}
+
+/**
+ * Read a field mask and apply each masked value as a delta onto `fields`
+ * (keyframes start from zeros)
+ */
+function readFields(reader: Reader, fields: number[]): void {
+  const mask = reader.varint();
+  if (mask >= 1 << FIELD_COUNT) throw new Error('Unknown runner field in mask');
+  for (let i = 0; i < FIELD_COUNT; i++) {
+    if (mask & (1 << i)) fields[i] += reader.svarint();
+  }
+}
+
+function toSnapshot(runner: QuantizedRunner): RunnerSnapshot {
+  const f = runner.fields;
+  const value = (i: number) => f[i] / SCALES[i];
+  const flags = f[5];
+
+  const snapshot: RunnerSnapshot = {
+    id: runner.id,
+    distance: value(0),
+    lane_position: value(1),
+    speed: value(2),
+    animation_phase: value(3),
+    energy: value(4),
+    finished: (flags & FLAG_FINISHED) !== 0,
+    drafting: (flags & FLAG_DRAFTING) !== 0,
+    kicking: (flags & FLAG_KICKING) !== 0,
+    player: (flags & FLAG_PLAYER) !== 0,
+    lane: f[6],
+    finish_distance: value(7),
+    place: f[11],
+    gap: value(12),
+    gap_time: value(13),
+    interval: value(14),
+    interval_time: value(15),
+  };
+
+  if (flags & FLAG_WORLD) {
+    snapshot.world = {
+      x: value(8),
+      z: value(9),
+      rotation: value(10),
+      section: flags & FLAG_BEND ? 'Bend' : 'Straight',
+    };
+  }
+
+  return snapshot;
+}
@@ -10,12 +10,49 @@ export {
   isTauri,
   type RaceStatus,
   type GameState,
+  type GameCommand,
   type RunnerSnapshot,
+  type TrackPosition,
+  type TrackSection,
+  type StartFormation,
   type RaceSnapshot,
+  type TickResult,
+  type RaceEvent,
+  type RaceEvents,
+  type PlaceChange,
+  type Phrase,
+  type CommentaryLine,
   type RaceResult,
+  type RecordedSplit,
+  type RunnerSplits,
+  type SplitInterval,
+  type TimingPoints,
+  type PacingStrategy,
+  type PacingMix,
+  type Athlete,
+  type Kit,
+  type PersonalBest,
+  type Entrant,
+  type TieBreak,
+  type SeriesEvent,
+  type SeriesInfo,
+  type Standing,
+  type ResultFlag,
+  type AthleteId,
+  type Mark,
+  type RaceSummary,
+  type HistoryEntry,
   type ServerStats,
+  type GameError,
+  type GameErrorCode,
+  type Unsubscribe,
+  type ReplayInfo,
+  type ClientMessage,
+  type ServerMessage,
 } from './GameServerBridge';
 
+export { SnapshotDecoder, type DecodedFrame } from './SnapshotDecoder';
+
 export {
   RustWorldAdapter,
   getRustWorldAdapter,